@@ -4,28 +4,49 @@
 use core::panic;
 use std::{
     collections::BTreeMap,
-    fs, io,
+    fmt, fs,
+    io::{self, BufRead, Write},
     path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use console::Style;
 use serde::{Deserialize, Serialize};
 
+use self::retention::{LogRetention, LogSummary};
+use self::schedule::Schedule;
 use crate::{
     devices::Device,
-    storages::{StorageExt, Storages},
+    hooks::Hooks,
+    storages::{Storage, StorageExt, Storages},
 };
 
+pub mod retention;
+pub mod schedule;
+
 /// Directory to store backup configs for each devices.
 pub const BACKUPSDIR: &str = "backups";
 
+/// Directory to store backup logs for each devices.
+pub const LOGSDIR: &str = "logs";
+
 /// File to store backups for the `device`.
 /// Relative path from the config directory.
 pub fn backups_file(device: &Device) -> PathBuf {
     PathBuf::from(BACKUPSDIR).join(format!("{}.yml", device.name()))
 }
 
+/// Append-only file to store logs of the backup `backup_name` on the `device`.
+/// Each line is a JSON of [`BackupLog`].
+/// Relative path from the config directory.
+pub fn logs_file(device: &Device, backup_name: &str) -> PathBuf {
+    PathBuf::from(LOGSDIR)
+        .join(device.name())
+        .join(format!("{}.jsonl", backup_name))
+}
+
 /// Targets for backup source or destination.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupTarget {
@@ -55,30 +76,87 @@ impl BackupTarget {
         let parent_path = parent.mount_path(device)?;
         Some(parent_path.join(self.path.clone().iter().collect::<PathBuf>()))
     }
+
+    /// Location of the [`BackupTarget`] to show or to pass to the backup command.
+    /// Remote path like `gdrive:backup` for [`Storage::Rclone`],
+    /// `<archive path>::<path in the archive>` for [`Storage::Archive`],
+    /// `<label>:/<path>` for [`Storage::Offline`] and full path for others.
+    /// Locations of archives and offline media are only for display,
+    /// since backup tools don't accept them.
+    pub fn location(&self, storages: &Storages, device: &Device) -> Option<String> {
+        match storages.get(&self.storage)? {
+            Storage::Rclone(remote) => Some(remote.remote_path(&self.path)),
+            Storage::Offline(media) => Some(media.media_path(&self.path)),
+            Storage::Archive(archive) => {
+                let archive_path = archive.path(storages, device)?.display().to_string();
+                let inner = self.path.join("/");
+                if inner.is_empty() {
+                    Some(archive_path)
+                } else {
+                    Some(format!("{}::{}", archive_path, inner))
+                }
+            }
+            _ => self
+                .path(storages, device)
+                .map(|path| path.display().to_string()),
+        }
+    }
 }
 
 /// Type of backup commands.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BackupCommand {
     ExternallyInvoked(ExternallyInvoked),
+    Shell(ShellCommand),
+    Rclone(RcloneCommand),
 }
 
 pub trait BackupCommandExt {
     fn name(&self) -> &String;
 
     fn note(&self) -> &String;
+
+    /// Mapping from exit codes to [`BackupResult`]s specific to the command.
+    fn exit_codes(&self) -> &BTreeMap<u64, BackupResult>;
 }
 
 impl BackupCommandExt for BackupCommand {
     fn name(&self) -> &String {
         match self {
             BackupCommand::ExternallyInvoked(cmd) => cmd.name(),
+            BackupCommand::Shell(cmd) => cmd.name(),
+            BackupCommand::Rclone(cmd) => cmd.name(),
         }
     }
 
     fn note(&self) -> &String {
         match self {
             BackupCommand::ExternallyInvoked(cmd) => cmd.note(),
+            BackupCommand::Shell(cmd) => cmd.note(),
+            BackupCommand::Rclone(cmd) => cmd.note(),
+        }
+    }
+
+    fn exit_codes(&self) -> &BTreeMap<u64, BackupResult> {
+        match self {
+            BackupCommand::ExternallyInvoked(cmd) => cmd.exit_codes(),
+            BackupCommand::Shell(cmd) => cmd.exit_codes(),
+            BackupCommand::Rclone(cmd) => cmd.exit_codes(),
+        }
+    }
+}
+
+impl BackupCommand {
+    /// Set mapping from exit codes to [`BackupResult`]s.
+    pub fn with_exit_codes(self, exit_codes: BTreeMap<u64, BackupResult>) -> Self {
+        match self {
+            BackupCommand::ExternallyInvoked(cmd) => {
+                BackupCommand::ExternallyInvoked(ExternallyInvoked { exit_codes, ..cmd })
+            }
+            BackupCommand::Shell(cmd) => BackupCommand::Shell(ShellCommand { exit_codes, ..cmd }),
+            BackupCommand::Rclone(cmd) => {
+                BackupCommand::Rclone(RcloneCommand { exit_codes, ..cmd })
+            }
         }
     }
 }
@@ -89,11 +167,17 @@ impl BackupCommandExt for BackupCommand {
 pub struct ExternallyInvoked {
     name: String,
     pub note: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    exit_codes: BTreeMap<u64, BackupResult>,
 }
 
 impl ExternallyInvoked {
     pub fn new(name: String, note: String) -> Self {
-        ExternallyInvoked { name, note }
+        ExternallyInvoked {
+            name,
+            note,
+            exit_codes: BTreeMap::new(),
+        }
     }
 }
 
@@ -105,6 +189,112 @@ impl BackupCommandExt for ExternallyInvoked {
     fn note(&self) -> &String {
         &self.note
     }
+
+    fn exit_codes(&self) -> &BTreeMap<u64, BackupResult> {
+        &self.exit_codes
+    }
+}
+
+/// Backup commands which is executed in the shell by `backup run`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellCommand {
+    name: String,
+    /// Command line passed to the shell.
+    pub command: String,
+    pub note: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    exit_codes: BTreeMap<u64, BackupResult>,
+}
+
+impl ShellCommand {
+    pub fn new(name: String, command: String, note: String) -> Self {
+        ShellCommand {
+            name,
+            command,
+            note,
+            exit_codes: BTreeMap::new(),
+        }
+    }
+}
+
+impl BackupCommandExt for ShellCommand {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn note(&self) -> &String {
+        &self.note
+    }
+
+    fn exit_codes(&self) -> &BTreeMap<u64, BackupResult> {
+        &self.exit_codes
+    }
+}
+
+/// How `rclone` transfers the files.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RcloneMode {
+    /// Copy new or changed files. Files only in the destination are kept.
+    Copy,
+    /// Make the destination identical to the source, deleting files only in the destination.
+    Sync,
+}
+
+impl fmt::Display for RcloneMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RcloneMode::Copy => write!(f, "copy"),
+            RcloneMode::Sync => write!(f, "sync"),
+        }
+    }
+}
+
+/// Built-in backup command which runs `rclone copy` or `rclone sync` by `backup run`.
+/// The destination can be an rclone remote storage as well as local paths.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RcloneCommand {
+    name: String,
+    pub mode: RcloneMode,
+    /// Additional arguments to rclone, like `--exclude`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    pub note: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    exit_codes: BTreeMap<u64, BackupResult>,
+}
+
+impl RcloneCommand {
+    pub fn new(name: String, mode: RcloneMode, args: Vec<String>, note: String) -> Self {
+        RcloneCommand {
+            name,
+            mode,
+            args,
+            note,
+            exit_codes: BTreeMap::new(),
+        }
+    }
+
+    /// Arguments to rclone to back up `src` to `dest`.
+    pub fn rclone_args(&self, src: &str, dest: &str) -> Vec<String> {
+        let mut args = vec![self.mode.to_string(), src.to_string(), dest.to_string()];
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+impl BackupCommandExt for RcloneCommand {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn note(&self) -> &String {
+        &self.note
+    }
+
+    fn exit_codes(&self) -> &BTreeMap<u64, BackupResult> {
+        &self.exit_codes
+    }
 }
 
 /// Backup execution log.
@@ -112,38 +302,127 @@ impl BackupCommandExt for ExternallyInvoked {
 pub struct BackupLog {
     pub datetime: DateTime<Local>,
     status: BackupResult,
+    /// Raw exit code of the backup command, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exit_code: Option<u64>,
+    /// Time taken by the backup in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    /// Size of the backed up data in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
     log: String,
 }
 
 impl BackupLog {
-    pub fn new_with_current_time(status: BackupResult, log: String) -> BackupLog {
+    pub fn new_with_current_time(
+        status: BackupResult,
+        exit_code: Option<u64>,
+        log: String,
+    ) -> BackupLog {
         let timestamp = Local::now();
         trace!("Generating timestamp: {:?}", timestamp);
         BackupLog {
             datetime: timestamp,
             status,
+            exit_code,
+            duration: None,
+            bytes: None,
             log,
         }
     }
+
+    /// Set time taken by the backup in seconds.
+    pub fn with_duration(self, duration: Option<u64>) -> Self {
+        BackupLog { duration, ..self }
+    }
+
+    /// Set size of the backed up data in bytes.
+    pub fn with_bytes(self, bytes: Option<u64>) -> Self {
+        BackupLog { bytes, ..self }
+    }
+
+    pub fn status(&self) -> &BackupResult {
+        &self.status
+    }
+
+    pub fn exit_code(&self) -> Option<u64> {
+        self.exit_code
+    }
+
+    pub fn duration(&self) -> Option<u64> {
+        self.duration
+    }
+
+    pub fn bytes(&self) -> Option<u64> {
+        self.bytes
+    }
+
+    pub fn log(&self) -> &String {
+        &self.log
+    }
 }
 
 /// Result of backup.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum BackupResult {
+    /// Completed without any problem.
     Success,
+    /// Completed, but with some warnings.
+    Warning,
+    /// Some files were not transferred.
+    Partial,
+    /// Not performed, e.g. because there was nothing to do.
+    Skipped,
+    /// Interrupted by the user or a signal.
+    Cancelled,
     Failure,
 }
 
 impl BackupResult {
-    pub fn from_exit_code(code: u64) -> Self {
-        if code == 0 {
-            Self::Success
-        } else {
-            Self::Failure
+    /// Convert `code` to [`BackupResult`] with command specific `mapping`.
+    /// Codes not in `mapping` are [`BackupResult::Success`] if 0, otherwise [`BackupResult::Failure`].
+    pub fn from_exit_code(code: u64, mapping: &BTreeMap<u64, BackupResult>) -> Self {
+        match mapping.get(&code) {
+            Some(result) => *result,
+            None if code == 0 => Self::Success,
+            None => Self::Failure,
+        }
+    }
+
+    /// Whether the data was backed up completely.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, BackupResult::Success | BackupResult::Warning)
+    }
+
+    pub fn style(&self) -> Style {
+        match self {
+            BackupResult::Success => Style::new().green(),
+            BackupResult::Warning => Style::new().yellow(),
+            BackupResult::Partial => Style::new().magenta(),
+            BackupResult::Skipped => Style::new().bright().black(),
+            BackupResult::Cancelled => Style::new().blue(),
+            BackupResult::Failure => Style::new().red().bold(),
         }
     }
 }
 
+impl fmt::Display for BackupResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Parse exit code mapping like `24=partial`.
+pub fn parse_exit_code_mapping(s: &str) -> Result<(u64, BackupResult)> {
+    let (code, result) = s
+        .split_once('=')
+        .context("Exit code mapping must be in the form of CODE=RESULT")?;
+    let code = code.trim().parse().context("Invalid exit code")?;
+    let result = BackupResult::from_str(result.trim(), true).map_err(|e| anyhow!(e))?;
+    Ok((code, result))
+}
+
 /// Backup source, destination, command and logs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Backup {
@@ -154,7 +433,28 @@ pub struct Backup {
     from: BackupTarget,
     to: BackupTarget,
     command: BackupCommand,
+    /// Hooks run by `backup run`.
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    hooks: Hooks,
+    /// Policy to prune old logs automatically.
+    #[serde(default, skip_serializing_if = "LogRetention::is_empty")]
+    retention: LogRetention,
+    /// How often the backup should run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<Schedule>,
+    /// Stored in [`logs_file`]. Only read for the backward compatibility.
+    #[serde(default, skip_serializing)]
     logs: Vec<BackupLog>,
+    /// Compacted statistics of pruned logs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    summaries: Vec<LogSummary>,
+    /// Logs added since read, to be appended to [`logs_file`].
+    #[serde(skip)]
+    new_logs: Vec<BackupLog>,
+    /// Whether [`logs_file`] should be rewritten instead of appended,
+    /// like when logs are pruned or moved.
+    #[serde(skip)]
+    rewrite_logs: bool,
 }
 
 impl Backup {
@@ -172,10 +472,34 @@ impl Backup {
             from,
             to,
             command,
+            hooks: Hooks::default(),
+            retention: LogRetention::default(),
+            schedule: None,
             logs: Vec::new(),
+            summaries: Vec::new(),
+            new_logs: Vec::new(),
+            rewrite_logs: false,
+        }
+    }
+
+    /// Set hooks for the backup.
+    pub fn with_hooks(self, hooks: Hooks) -> Self {
+        Backup { hooks, ..self }
+    }
+
+    /// Set mapping from exit codes of the command to [`BackupResult`]s.
+    pub fn with_exit_codes(self, exit_codes: BTreeMap<u64, BackupResult>) -> Self {
+        Backup {
+            command: self.command.with_exit_codes(exit_codes),
+            ..self
         }
     }
 
+    /// Set schedule for the backup.
+    pub fn with_schedule(self, schedule: Option<Schedule>) -> Self {
+        Backup { schedule, ..self }
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
@@ -184,6 +508,13 @@ impl Backup {
         devices.iter().find(|dev| dev.name() == self.device)
     }
 
+    /// Set name of [`crate::Device`] on renaming the device.
+    /// Logs are written to the [`logs_file`] of the new device as a whole.
+    pub fn set_device_name(&mut self, device_name: String) {
+        self.device = device_name;
+        self.rewrite_logs = true;
+    }
+
     pub fn source(&self) -> &BackupTarget {
         &self.from
     }
@@ -196,14 +527,81 @@ impl Backup {
         &self.command
     }
 
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    pub fn retention(&self) -> &LogRetention {
+        &self.retention
+    }
+
+    pub fn set_retention(&mut self, retention: LogRetention) {
+        self.retention = retention
+    }
+
+    pub fn schedule(&self) -> Option<&Schedule> {
+        self.schedule.as_ref()
+    }
+
+    pub fn set_schedule(&mut self, schedule: Option<Schedule>) {
+        self.schedule = schedule;
+    }
+
+    /// Add new log and prune old logs with the configured retention.
     pub fn add_log(&mut self, newlog: BackupLog) {
-        self.logs.push(newlog)
+        self.new_logs.push(newlog.clone());
+        self.logs.push(newlog);
+        let retention = self.retention.clone();
+        let n_pruned = self.prune_logs(&retention);
+        if n_pruned > 0 {
+            info!("Pruned {} logs of {}", n_pruned, self.name);
+        }
+    }
+
+    /// Prune logs not matched to `retention` and compact them into summaries.
+    /// Returns the number of pruned logs.
+    pub fn prune_logs(&mut self, retention: &LogRetention) -> usize {
+        let logs = std::mem::take(&mut self.logs);
+        let (kept, pruned) = retention.split(logs, Local::now());
+        retention::compact(&mut self.summaries, &pruned);
+        self.logs = kept;
+        if !pruned.is_empty() {
+            self.rewrite_logs = true;
+        }
+        pruned.len()
+    }
+
+    /// Number of all recorded runs and successful ones, including pruned logs.
+    pub fn count_runs(&self) -> (usize, usize) {
+        let n_all = self.logs.len() + self.summaries.iter().map(|s| s.count).sum::<usize>();
+        let n_ok = self.logs.iter().filter(|log| log.status().is_ok()).count()
+            + self
+                .summaries
+                .iter()
+                .flat_map(|s| s.results.iter())
+                .filter(|(result, _)| result.is_ok())
+                .map(|(_, n)| n)
+                .sum::<usize>();
+        (n_all, n_ok)
+    }
+
+    /// Logs sorted by datetime.
+    pub fn logs(&self) -> &Vec<BackupLog> {
+        &self.logs
     }
 
     /// Get the last backup.
     pub fn last_backup(&self) -> Option<&BackupLog> {
         self.logs.iter().max_by_key(|log| log.datetime)
     }
+
+    /// Get the last successful backup, which is never pruned by [`LogRetention`].
+    pub fn last_success(&self) -> Option<&BackupLog> {
+        self.logs
+            .iter()
+            .filter(|log| log.status().is_ok())
+            .max_by_key(|log| log.datetime)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -245,6 +643,8 @@ impl Backups {
         }
     }
 
+    /// Load [`Backups`] of `device` and merge logs from [`logs_file`]s.
+    /// Logs in the backups file (old format) are also loaded.
     pub fn read(config_dir: &Path, device: &Device) -> Result<Backups> {
         let backups_file = config_dir.join(backups_file(device));
         if !backups_file.exists() {
@@ -253,18 +653,181 @@ impl Backups {
         trace!("Reading {}", backups_file.display());
         let f = fs::File::open(backups_file)?;
         let reader = io::BufReader::new(f);
-        let yaml: Backups =
+        let mut yaml: Backups =
             serde_yaml::from_reader(reader).context("Failed to parse backups file")?;
+        for backup in yaml.list.values_mut() {
+            // logs in the old format are moved to the logs file
+            backup.rewrite_logs = !backup.logs.is_empty();
+            let logs = read_logs(&config_dir.join(logs_file(device, &backup.name)))?;
+            backup.logs.extend(logs);
+            backup.logs.sort_by_key(|log| log.datetime);
+            backup.logs.dedup_by_key(|log| log.datetime);
+        }
         Ok(yaml)
     }
 
-    pub fn write(self, config_dir: &Path, device: &Device) -> Result<()> {
+    /// Load backups of all `devices` with their device names.
+    /// Devices without the backups file are skipped.
+    pub fn read_all(config_dir: &Path, devices: &[Device]) -> Result<Vec<(String, Backup)>> {
+        let mut all_backups = vec![];
+        for device in devices {
+            if !config_dir.join(backups_file(device)).exists() {
+                continue;
+            }
+            let backups = Backups::read(config_dir, device)?;
+            all_backups.extend(
+                backups
+                    .list
+                    .into_values()
+                    .map(|backup| (device.name(), backup)),
+            );
+        }
+        Ok(all_backups)
+    }
+
+    /// Write config to the backups file and logs to [`logs_file`]s.
+    /// New logs are appended, and the whole logs are written only if pruned or moved.
+    /// Returns relative paths of the written files.
+    pub fn write(self, config_dir: &Path, device: &Device) -> Result<Vec<PathBuf>> {
+        let mut written = vec![backups_file(device)];
         let f = fs::File::create(config_dir.join(backups_file(device)))
             .context("Failed to open backups file")?;
         let writer = io::BufWriter::new(f);
         serde_yaml::to_writer(writer, &self).context(format!(
             "Failed writing to {}",
             config_dir.join(backups_file(device)).display()
-        ))
+        ))?;
+        for backup in self.list.values() {
+            let logs_file = logs_file(device, &backup.name);
+            if backup.rewrite_logs {
+                write_logs(&config_dir.join(&logs_file), &backup.logs)?;
+            } else if !backup.new_logs.is_empty() {
+                append_logs(&config_dir.join(&logs_file), &backup.new_logs)?;
+            } else {
+                continue;
+            }
+            written.push(logs_file);
+        }
+        Ok(written)
+    }
+}
+
+/// Read [`BackupLog`]s from JSON lines file.
+/// Empty if the file doesn't exist.
+fn read_logs(path: &Path) -> Result<Vec<BackupLog>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    trace!("Reading {}", path.display());
+    let f = fs::File::open(path)?;
+    io::BufReader::new(f)
+        .lines()
+        .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|line| {
+            serde_json::from_str(&line?).context(format!("Failed to parse {}", path.display()))
+        })
+        .collect()
+}
+
+/// Write `logs` to JSON lines file, one log per line.
+/// Existing logs in the file are overwritten.
+fn write_logs(path: &Path, logs: &[BackupLog]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let f = fs::File::create(path).context(format!("Failed to open {}", path.display()))?;
+    write_log_lines(f, logs)
+}
+
+/// Append `logs` to JSON lines file, keeping the existing lines untouched.
+fn append_logs(path: &Path, logs: &[BackupLog]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let f = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(format!("Failed to open {}", path.display()))?;
+    write_log_lines(f, logs)
+}
+
+fn write_log_lines(f: fs::File, logs: &[BackupLog]) -> Result<()> {
+    let mut writer = io::BufWriter::new(f);
+    for log in logs {
+        serde_json::to_writer(&mut writer, log)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+
+    use super::{
+        BackupLog, BackupResult, append_logs, parse_exit_code_mapping, read_logs, write_logs,
+    };
+
+    #[test]
+    fn exit_code_mapping() {
+        let mapping = BTreeMap::from([parse_exit_code_mapping("24=partial").unwrap()]);
+        assert_eq!(
+            BackupResult::from_exit_code(0, &mapping),
+            BackupResult::Success
+        );
+        assert_eq!(
+            BackupResult::from_exit_code(24, &mapping),
+            BackupResult::Partial
+        );
+        assert_eq!(
+            BackupResult::from_exit_code(1, &mapping),
+            BackupResult::Failure
+        );
+        assert!(parse_exit_code_mapping("24").is_err());
+        assert!(parse_exit_code_mapping("24=unknown").is_err());
+    }
+
+    #[test]
+    fn logs_roundtrip() -> Result<()> {
+        let dir = assert_fs::TempDir::new()?;
+        let path = dir.path().join("logs").join("dev").join("backup.jsonl");
+        assert!(read_logs(&path)?.is_empty());
+        let logs = vec![
+            BackupLog::new_with_current_time(BackupResult::Success, Some(0), "".to_string()),
+            BackupLog::new_with_current_time(
+                BackupResult::Failure,
+                None,
+                "multi\nline".to_string(),
+            ),
+        ];
+        write_logs(&path, &logs)?;
+        assert_eq!(std::fs::read_to_string(&path)?.lines().count(), 2);
+        let read = read_logs(&path)?;
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[1].status(), &BackupResult::Failure);
+        assert_eq!(read[1].log(), "multi\nline");
+
+        // existing lines are kept as they are
+        let first_line = std::fs::read_to_string(&path)?
+            .lines()
+            .next()
+            .unwrap()
+            .to_string();
+        append_logs(
+            &path,
+            &[BackupLog::new_with_current_time(
+                BackupResult::Warning,
+                Some(24),
+                "".to_string(),
+            )],
+        )?;
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!(content.lines().next().unwrap(), first_line);
+        assert_eq!(read_logs(&path)?.len(), 3);
+        Ok(())
     }
 }
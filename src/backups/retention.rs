@@ -0,0 +1,183 @@
+//! Retention of backup logs and compaction of pruned logs into summaries.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use super::{BackupLog, BackupResult};
+
+/// Policy to prune old [`BackupLog`]s.
+/// A log is kept if it satisfies any of the conditions.
+/// The latest log and the latest successful log are always kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogRetention {
+    /// Keep the last `keep_last` logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+    /// Keep logs younger than `max_age_days` days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+}
+
+impl LogRetention {
+    pub fn new(keep_last: Option<usize>, max_age_days: Option<u64>) -> Self {
+        LogRetention {
+            keep_last,
+            max_age_days,
+        }
+    }
+
+    /// No logs are pruned.
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none() && self.max_age_days.is_none()
+    }
+
+    /// Split `logs` into kept ones and pruned ones, based on the time `now`.
+    /// Both of them are sorted by datetime.
+    pub fn split(
+        &self,
+        mut logs: Vec<BackupLog>,
+        now: DateTime<Local>,
+    ) -> (Vec<BackupLog>, Vec<BackupLog>) {
+        logs.sort_by_key(|log| log.datetime);
+        if self.is_empty() {
+            return (logs, vec![]);
+        }
+        let n_logs = logs.len();
+        let last_ok = logs.iter().rposition(|log| log.status().is_ok());
+        let (kept, pruned) = logs.into_iter().enumerate().partition(|(i, log)| {
+            let is_last = *i + 1 == n_logs || Some(*i) == last_ok;
+            let in_last_n = self.keep_last.is_some_and(|n| *i + n >= n_logs);
+            let is_young = self
+                .max_age_days
+                .is_some_and(|days| now - log.datetime < TimeDelta::days(days as i64));
+            is_last || in_last_n || is_young
+        });
+        let strip = |logs: Vec<(usize, BackupLog)>| logs.into_iter().map(|(_, log)| log).collect();
+        (strip(kept), strip(pruned))
+    }
+}
+
+/// Aggregated statistics of pruned logs in a month.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogSummary {
+    /// Month of the logs like "2024-05".
+    pub period: String,
+    /// Number of logs.
+    pub count: usize,
+    /// Number of logs for each result.
+    pub results: BTreeMap<BackupResult, usize>,
+    /// Datetime of the first log.
+    pub first: DateTime<Local>,
+    /// Datetime of the last log.
+    pub last: DateTime<Local>,
+}
+
+impl LogSummary {
+    fn from_log(log: &BackupLog) -> Self {
+        LogSummary {
+            period: log.datetime.format("%Y-%m").to_string(),
+            count: 1,
+            results: BTreeMap::from([(*log.status(), 1)]),
+            first: log.datetime,
+            last: log.datetime,
+        }
+    }
+
+    fn add(&mut self, log: &BackupLog) {
+        self.count += 1;
+        *self.results.entry(*log.status()).or_insert(0) += 1;
+        self.first = self.first.min(log.datetime);
+        self.last = self.last.max(log.datetime);
+    }
+}
+
+/// Merge `logs` into monthly `summaries`.
+pub fn compact(summaries: &mut Vec<LogSummary>, logs: &[BackupLog]) {
+    for log in logs {
+        let period = log.datetime.format("%Y-%m").to_string();
+        match summaries.iter_mut().find(|s| s.period == period) {
+            Some(summary) => summary.add(log),
+            None => summaries.push(LogSummary::from_log(log)),
+        }
+    }
+    summaries.sort_by(|a, b| a.period.cmp(&b.period));
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Local, TimeDelta};
+
+    use super::{LogRetention, compact};
+    use crate::backups::{BackupLog, BackupResult};
+
+    fn logs(days_ago: &[i64]) -> Vec<BackupLog> {
+        days_ago
+            .iter()
+            .map(|days| {
+                let mut log = BackupLog::new_with_current_time(
+                    BackupResult::Success,
+                    Some(0),
+                    "".to_string(),
+                );
+                log.datetime -= TimeDelta::days(*days);
+                log
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_logs() {
+        let now = Local::now();
+        let all = logs(&[100, 50, 40, 20, 10, 1]);
+
+        let (kept, pruned) = LogRetention::default().split(all.clone(), now);
+        assert_eq!((kept.len(), pruned.len()), (6, 0));
+
+        let (kept, pruned) = LogRetention::new(Some(2), None).split(all.clone(), now);
+        assert_eq!((kept.len(), pruned.len()), (2, 4));
+
+        let (kept, pruned) = LogRetention::new(None, Some(30)).split(all.clone(), now);
+        assert_eq!((kept.len(), pruned.len()), (3, 3));
+
+        let (kept, pruned) = LogRetention::new(Some(4), Some(30)).split(all.clone(), now);
+        assert_eq!((kept.len(), pruned.len()), (4, 2));
+
+        // the latest log is always kept
+        let (kept, pruned) = LogRetention::new(Some(0), Some(0)).split(all.clone(), now);
+        assert_eq!((kept.len(), pruned.len()), (1, 5));
+
+        // so is the latest successful one
+        let mut failed = all;
+        for log in failed.iter_mut().skip(4) {
+            *log = BackupLog::new_with_current_time(BackupResult::Failure, None, "".to_string());
+        }
+        let (kept, pruned) = LogRetention::new(Some(1), None).split(failed.clone(), now);
+        assert_eq!((kept.len(), pruned.len()), (2, 4));
+        assert_eq!(kept[0].datetime, failed[3].datetime);
+        assert!(kept[0].status().is_ok());
+    }
+
+    #[test]
+    fn compact_logs() {
+        let all = logs(&[100, 99, 1]);
+        let mut summaries = vec![];
+        compact(&mut summaries, &all[..2]);
+        compact(&mut summaries, &all[2..]);
+        assert_eq!(
+            summaries.iter().map(|s| s.count).sum::<usize>(),
+            3,
+            "{:?}",
+            summaries
+        );
+        assert!(summaries.windows(2).all(|w| w[0].period < w[1].period));
+        assert_eq!(
+            summaries
+                .iter()
+                .map(|s| s.results[&BackupResult::Success])
+                .sum::<usize>(),
+            3
+        );
+    }
+}
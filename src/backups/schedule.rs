@@ -0,0 +1,158 @@
+//! Schedules of backups.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{Context, anyhow};
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+
+/// How often the backup should run.
+/// Written as `hourly`, `daily`, `weekly`, `monthly`, `<N>h` or `<N>d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Schedule {
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    /// Every N hours, less than a day.
+    Hours(u32),
+    /// Every N days.
+    /// Calendar based timers restart the count at the beginning of each month.
+    Days(u32),
+}
+
+impl Schedule {
+    /// Expected interval between the runs.
+    pub fn interval(&self) -> TimeDelta {
+        match self {
+            Schedule::Hourly => TimeDelta::hours(1),
+            Schedule::Daily => TimeDelta::days(1),
+            Schedule::Weekly => TimeDelta::weeks(1),
+            Schedule::Monthly => TimeDelta::days(30),
+            Schedule::Hours(n) => TimeDelta::hours(*n as i64),
+            Schedule::Days(n) => TimeDelta::days(*n as i64),
+        }
+    }
+
+    /// `OnCalendar=` value of systemd timers.
+    pub fn on_calendar(&self) -> String {
+        match self {
+            Schedule::Hourly => "hourly".to_string(),
+            Schedule::Daily => "daily".to_string(),
+            Schedule::Weekly => "weekly".to_string(),
+            Schedule::Monthly => "monthly".to_string(),
+            Schedule::Hours(n) => format!("*-*-* 00/{}:00:00", n),
+            Schedule::Days(n) => format!("*-*-01/{} 00:00:00", n),
+        }
+    }
+
+    /// Time and date fields of crontab.
+    pub fn cron(&self) -> String {
+        match self {
+            Schedule::Hourly => "0 * * * *".to_string(),
+            Schedule::Daily => "0 0 * * *".to_string(),
+            Schedule::Weekly => "0 0 * * 1".to_string(),
+            Schedule::Monthly => "0 0 1 * *".to_string(),
+            Schedule::Hours(n) => format!("0 */{} * * *", n),
+            Schedule::Days(n) => format!("0 0 */{} * *", n),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_n = |n: &str| -> anyhow::Result<u32> {
+            let n = n
+                .parse()
+                .context(format!("Invalid number in schedule {}", s))?;
+            if n == 0 {
+                return Err(anyhow!("Interval of schedule {} must be positive", s));
+            }
+            Ok(n)
+        };
+        match s {
+            "hourly" => Ok(Schedule::Hourly),
+            "daily" => Ok(Schedule::Daily),
+            "weekly" => Ok(Schedule::Weekly),
+            "monthly" => Ok(Schedule::Monthly),
+            _ => {
+                if let Some(n) = s.strip_suffix('h') {
+                    let n = parse_n(n)?;
+                    if n >= 24 {
+                        return Err(anyhow!("Use days instead of {}", s));
+                    }
+                    Ok(Schedule::Hours(n))
+                } else if let Some(n) = s.strip_suffix('d') {
+                    Ok(Schedule::Days(parse_n(n)?))
+                } else {
+                    Err(anyhow!(
+                        "Invalid schedule {}. Use hourly, daily, weekly, monthly, <N>h or <N>d",
+                        s
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(value: Schedule) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Hourly => write!(f, "hourly"),
+            Schedule::Daily => write!(f, "daily"),
+            Schedule::Weekly => write!(f, "weekly"),
+            Schedule::Monthly => write!(f, "monthly"),
+            Schedule::Hours(n) => write!(f, "{}h", n),
+            Schedule::Days(n) => write!(f, "{}d", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeDelta;
+
+    use super::Schedule;
+
+    #[test]
+    fn parse_schedule() {
+        assert_eq!("daily".parse::<Schedule>().unwrap(), Schedule::Daily);
+        assert_eq!("6h".parse::<Schedule>().unwrap(), Schedule::Hours(6));
+        assert_eq!("3d".parse::<Schedule>().unwrap(), Schedule::Days(3));
+        assert!("0d".parse::<Schedule>().is_err());
+        assert!("48h".parse::<Schedule>().is_err());
+        assert!("often".parse::<Schedule>().is_err());
+        for s in ["hourly", "weekly", "monthly", "12h", "10d"] {
+            assert_eq!(s.parse::<Schedule>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn schedule_formats() {
+        assert_eq!(Schedule::Hours(6).on_calendar(), "*-*-* 00/6:00:00");
+        assert_eq!(Schedule::Days(2).cron(), "0 0 */2 * *");
+        assert_eq!(Schedule::Weekly.interval(), TimeDelta::days(7));
+        assert_eq!(
+            serde_yaml::from_str::<Schedule>("2d").unwrap(),
+            Schedule::Days(2)
+        );
+        assert_eq!(serde_yaml::to_string(&Schedule::Daily).unwrap(), "daily\n");
+    }
+}
@@ -1,16 +1,22 @@
 //! CLI arguments
 
+use std::ffi::OsString;
+
 use crate::PathBuf;
 use crate::backups;
 use crate::devices;
+use crate::drives;
 use crate::path;
 use crate::storages;
-use clap::Args;
+use clap::{Args, ValueHint};
 use clap::{Parser, Subcommand};
 use clap_complete::ArgValueCandidates;
 use clap_complete::CompletionCandidate;
 use clap_verbosity_flag::Verbosity;
 
+/// Environment variable to specify the config dir.
+pub(crate) const CONFIG_DIR_ENV: &str = "XDBM_CONFIG_DIR";
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub(crate) struct Cli {
@@ -18,7 +24,7 @@ pub(crate) struct Cli {
     pub(crate) command: Commands,
 
     /// Customized config dir.
-    #[arg(short, long)]
+    #[arg(short, long, env = CONFIG_DIR_ENV)]
     pub(crate) config_dir: Option<PathBuf>,
 
     #[command(flatten)]
@@ -49,6 +55,14 @@ pub(crate) enum Commands {
     #[command(subcommand)]
     Backup(BackupSubCommands),
 
+    /// Manage devices.
+    #[command(subcommand)]
+    Device(DeviceSubCommands),
+
+    /// Manage disks, RAID arrays and pools which physical drives are on.
+    #[command(subcommand)]
+    Drive(DriveSubCommands),
+
     /// Print status for the given path.
     Status {
         /// Target path. Default is the current directory.
@@ -59,6 +73,9 @@ pub(crate) enum Commands {
         /// Show backup config covering the path.
         #[arg(short, long)]
         backup: bool,
+        /// Show where the copies of the path are.
+        #[arg(short, long)]
+        copies: bool,
     },
 
     /// Print config dir.
@@ -69,11 +86,17 @@ pub(crate) enum Commands {
         /// Remote name to sync.
         remote_name: Option<String>,
         /// Use custom git implementation.
-        #[arg(short, long)]
+        #[arg(short, long, overrides_with = "no_libgit2")]
         use_libgit2: bool,
+        /// Use git cli even if `use_libgit2` is set in the settings.
+        #[arg(long, overrides_with = "use_libgit2")]
+        no_libgit2: bool,
         /// Whether to use ssh-agent
-        #[arg(long)]
+        #[arg(long, overrides_with = "no_sshagent")]
         use_sshagent: bool,
+        /// Don't use ssh-agent even if `use_sshagent` is set in the settings.
+        #[arg(long, overrides_with = "use_sshagent")]
+        no_sshagent: bool,
         /// Manually specify ssh key
         #[arg(long)]
         ssh_key: Option<PathBuf>,
@@ -82,6 +105,61 @@ pub(crate) enum Commands {
     /// Check config files validity.
     Check {},
 
+    /// Interactive dashboard of storages, backups and logs.
+    Tui {},
+
+    /// Generate a report of storages and backups.
+    Report {
+        /// Write a static HTML site to this directory.
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        html: PathBuf,
+    },
+
+    /// Notify failed or overdue backups of this device to the sinks in notify.yml.
+    Notify {
+        /// Only print the notifications without sending them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export metrics of backups and storages.
+    Metrics {
+        /// Write a node_exporter textfile collector file (`.prom`) to this path.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        prometheus: PathBuf,
+    },
+
+    /// Keep running scheduled backups of this device when they are due
+    /// and their storages are mounted.
+    Daemon {
+        /// Seconds between the checks.
+        #[arg(long, default_value_t = 300)]
+        interval: u64,
+        /// Check and run due backups only once.
+        #[arg(long)]
+        once: bool,
+        /// Sync after running backups. Default is `auto_sync` in the settings.
+        #[arg(long)]
+        sync: bool,
+    },
+
+    /// Watch for drives to be mounted and run backups to them.
+    /// Drives are matched to physical storages by UUID or alias.
+    Watch {
+        /// Seconds between the checks.
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+
+    /// Use backup schedules with other schedulers.
+    #[command(subcommand)]
+    Schedule(ScheduleSubCommands),
+
+    /// Migrate config files to the current format.
+    ///
+    /// Moves backup logs out of `backups/<device>.yml` into `logs/<device>/<backup>.jsonl`.
+    Migrate {},
+
     /// [DEPRECATED] Generate completion script.
     ///
     /// Use xdbm native completion instead;
@@ -89,6 +167,86 @@ pub(crate) enum Commands {
     Completion { shell: clap_complete::Shell },
 }
 
+impl Commands {
+    /// Whether the command commits changes to the repository.
+    pub(crate) fn commits(&self) -> bool {
+        match self {
+            Commands::Storage(storage) => !matches!(storage.command, StorageCommands::List { .. }),
+            Commands::Backup(backup) => !matches!(
+                backup,
+                BackupSubCommands::List { .. } | BackupSubCommands::Preflight { .. }
+            ),
+            Commands::Device(device) => !matches!(device, DeviceSubCommands::List {}),
+            Commands::Drive(drive) => !matches!(drive, DriveSubCommands::List {}),
+            Commands::Migrate {} => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ScheduleSubCommands {
+    /// Generate systemd units or crontab entries running `backup run`
+    /// for the scheduled backups of this device.
+    #[command(group(clap::ArgGroup::new("format").required(true).args(["systemd", "cron"])))]
+    Export {
+        /// Generate `.service` and `.timer` units.
+        #[arg(long)]
+        systemd: bool,
+        /// Generate crontab entries.
+        #[arg(long)]
+        cron: bool,
+        /// Directory to write the units. Printed to stdout if not given.
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum DeviceSubCommands {
+    /// List all devices.
+    List {},
+    /// Rename the device.
+    /// Storages, backups and logs bound to the device are updated together.
+    Rename {
+        /// Current name of the device.
+        #[arg(add = ArgValueCandidates::new(device_name_completer))]
+        old_name: String,
+        /// New name of the device.
+        new_name: String,
+    },
+    /// Remove the device.
+    Remove {
+        /// Name of the device.
+        #[arg(add = ArgValueCandidates::new(device_name_completer))]
+        name: String,
+        /// Also unbind storages and remove backups of the device.
+        #[arg(long)]
+        cascade: bool,
+    },
+    /// Update OS and hostname of this device from the system.
+    Refresh {},
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum DriveSubCommands {
+    /// Add new disk, RAID array or pool.
+    Add {
+        /// Unique name for the drive.
+        name: String,
+        #[arg(short, long, value_enum, default_value_t = drives::DriveKind::Disk)]
+        kind: drives::DriveKind,
+        /// Member drives of the RAID array or pool. Add the members first.
+        #[arg(short, long = "member", add = ArgValueCandidates::new(drive_name_completer))]
+        members: Vec<String>,
+        /// Additional info. Empty by default.
+        #[arg(short, long, default_value = "")]
+        notes: String,
+    },
+    /// List all drives and the physical drives on them.
+    List {},
+}
+
 #[derive(Args, Debug)]
 #[command(args_conflicts_with_subcommands = true)]
 pub(crate) struct StorageArgs {
@@ -119,6 +277,52 @@ pub(crate) enum StorageCommands {
         #[arg(short, long)]
         path: path::PathBuf,
     },
+    /// Record current used and free bytes of storages mounted on this device.
+    Refresh {},
+    /// Record that the physical drive or offline media is moved to `location`.
+    Move {
+        /// Name of the physical drive or offline media.
+        #[arg(add = ArgValueCandidates::new(storage_name_completer))]
+        storage: String,
+        /// Where it is kept now, like "home safe" or "parents' house".
+        location: String,
+        /// The location is away from the site of the original data.
+        #[arg(long)]
+        offsite: bool,
+    },
+    /// Set lifecycle info of the physical drive.
+    #[command(group(clap::ArgGroup::new("info").required(true).multiple(true).args(["serial", "model", "purchased", "warranty_end", "status", "drive"])))]
+    Edit {
+        /// Name of the physical drive.
+        #[arg(add = ArgValueCandidates::new(storage_name_completer))]
+        storage: String,
+        /// Serial number of the drive.
+        #[arg(long)]
+        serial: Option<String>,
+        /// Model name of the drive.
+        #[arg(long)]
+        model: Option<String>,
+        /// Date of purchase, like 2026-01-03.
+        #[arg(long)]
+        purchased: Option<chrono::NaiveDate>,
+        /// Last day of the warranty, like 2029-01-03.
+        #[arg(long)]
+        warranty_end: Option<chrono::NaiveDate>,
+        #[arg(long, value_enum)]
+        status: Option<storages::physical_drive_partition::DriveStatus>,
+        /// Disk, RAID array or pool which the partition is on. See `drive add`.
+        #[arg(long, add = ArgValueCandidates::new(drive_name_completer))]
+        drive: Option<String>,
+    },
+    /// Mark the physical drive retired, so that `check` warns about backups still using it.
+    Retire {
+        /// Name of the physical drive.
+        #[arg(add = ArgValueCandidates::new(storage_name_completer))]
+        storage: String,
+        /// Mark the drive failed instead.
+        #[arg(long)]
+        failed: bool,
+    },
     // /// Remove storage from the storage list
     // Remove {
     //     storage: String,
@@ -131,15 +335,43 @@ pub(crate) struct StorageAddArgs {
     pub(crate) command: StorageAddCommands,
 }
 
+/// Fields of physical drive partition given by `storage add physical <path>`.
+#[derive(Args, Debug)]
+pub(crate) struct PhysicalDriveArgs {
+    /// Kind of the drive, like SSD.
+    #[arg(short, long)]
+    pub(crate) kind: Option<String>,
+    /// Capacity in bytes.
+    #[arg(short, long)]
+    pub(crate) capacity: Option<u64>,
+    /// Filesystem, like ext4.
+    #[arg(long)]
+    pub(crate) fs: Option<String>,
+    /// Whether the drive is removable.
+    #[arg(long)]
+    pub(crate) removable: Option<bool>,
+    /// Device specific alias for the storage.
+    #[arg(short, long)]
+    pub(crate) alias: Option<String>,
+}
+
 #[derive(Subcommand, Debug)]
 pub(crate) enum StorageAddCommands {
     /// Physical drive partition.
+    ///
+    /// Without `path` nor `--from-mount`, the drive is selected from system info interactively.
+    /// With `path`, the fields not given by the options are asked.
     Physical {
         /// Unique name for the storage.
         name: String,
         /// Path where the storage is mounted on this device.
         /// leave blank to fetch system info automatically.
         path: Option<PathBuf>,
+        /// Take the drive mounted at this path from system info without asking.
+        #[arg(long, value_hint = ValueHint::DirPath, conflicts_with_all = ["path", "kind", "capacity", "fs", "removable", "alias"])]
+        from_mount: Option<PathBuf>,
+        #[command(flatten)]
+        fields: PhysicalDriveArgs,
     },
     /// Sub directory of other storages.
     Directory {
@@ -170,6 +402,74 @@ pub(crate) enum StorageAddCommands {
         #[arg(short, long)]
         alias: String,
     },
+    /// Share on a file server like NAS.
+    Network {
+        /// Unique name for the storage.
+        name: String,
+        /// Path where the share is mounted on this device.
+        path: PathBuf,
+        /// Hostname or address of the server.
+        #[arg(short, long)]
+        server: String,
+        /// Share name or export path on the server.
+        #[arg(long)]
+        share: String,
+        /// Protocol to access the share.
+        #[arg(short, long, value_enum)]
+        protocol: storages::network_share::NetworkProtocol,
+        /// Capacity in bytes, if known.
+        #[arg(short, long)]
+        capacity: Option<u64>,
+        /// Device specific alias for the storage.
+        #[arg(short, long)]
+        alias: String,
+    },
+    /// Archive file, encrypted container or backup repository in other storages.
+    Archive {
+        /// Unique name for the storage.
+        name: String,
+        /// Path of the archive or repository on this device.
+        path: PathBuf,
+        /// Format of the archive or repository.
+        #[arg(short, long, value_enum)]
+        format: storages::archive::ArchiveFormat,
+        /// The content is encrypted.
+        #[arg(short, long)]
+        encrypted: bool,
+        /// ID of the repository, like restic's repository ID.
+        #[arg(long)]
+        repository_id: Option<String>,
+        /// Additional info. Empty by default.
+        #[arg(short, long, default_value = "")]
+        notes: String,
+    },
+    /// Offline media like optical discs and tapes, which are not mounted.
+    Offline {
+        /// Unique name for the storage.
+        name: String,
+        /// Kind of the media.
+        #[arg(short, long, value_enum)]
+        kind: storages::offline_media::MediaKind,
+        /// Label written on the media.
+        #[arg(short, long)]
+        label: String,
+        /// Where the media is kept.
+        #[arg(long)]
+        location: String,
+        /// Date when the media was written, like 2026-01-03. Today by default.
+        #[arg(short, long)]
+        written: Option<chrono::NaiveDate>,
+        /// Capacity in bytes.
+        #[arg(short, long)]
+        capacity: Option<u64>,
+    },
+    /// Remote configured in rclone.
+    Rclone {
+        /// Unique name for the storage.
+        name: String,
+        /// rclone remote with optional root path, like `gdrive:` or `b2:bucket/backup`.
+        remote: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -181,8 +481,29 @@ pub(crate) enum BackupSubCommands {
         #[arg(short, long)]
         src: PathBuf,
         /// Destination of the backuped data.
+        /// Relative to the storage if `--dest-storage` is given.
         #[arg(short, long)]
         dest: PathBuf,
+        /// Storage of the destination, for storages without local paths like rclone remotes or archives.
+        #[arg(long, add = ArgValueCandidates::new(storage_name_completer))]
+        dest_storage: Option<String>,
+        /// Add the backup even if the destination doesn't have enough free space.
+        #[arg(short, long)]
+        force: bool,
+        /// Shell command run before the backup by `backup run`. Can be given multiple times.
+        #[arg(long)]
+        pre_hook: Vec<String>,
+        /// Shell command run after the backup by `backup run`. Can be given multiple times.
+        #[arg(long)]
+        post_hook: Vec<String>,
+        /// Map exit code of the command to the result, like `24=partial`.
+        /// Can be given multiple times.
+        #[arg(long, value_parser = backups::parse_exit_code_mapping)]
+        exit_code: Vec<(u64, backups::BackupResult)>,
+        /// How often the backup should run:
+        /// hourly, daily, weekly, monthly, <N>h or <N>d.
+        #[arg(long)]
+        schedule: Option<backups::schedule::Schedule>,
         #[command(subcommand)]
         cmd: BackupAddCommands,
     },
@@ -207,11 +528,75 @@ pub(crate) enum BackupSubCommands {
         /// Name of the backup config.
         #[arg(add = ArgValueCandidates::new(backup_name_completer_local))]
         name: String,
-        /// Result of the backup
+        /// Exit code of the backup command.
         exit_status: u64,
+        /// Result of the backup. Determined from the exit code if not given.
+        #[arg(short, long)]
+        status: Option<backups::BackupResult>,
         /// Optional log or note about the backup execution.
         #[arg(short, long)]
         log: Option<String>,
+        /// Time taken by the backup in seconds.
+        #[arg(long)]
+        duration: Option<u64>,
+        /// Size of the backed up data in bytes.
+        #[arg(long)]
+        bytes: Option<u64>,
+    },
+    /// Run the backup command with hooks and record the result.
+    /// Environment variables `XDBM_BACKUP_NAME`, `XDBM_DEVICE`, `XDBM_BACKUP_SRC` and
+    /// `XDBM_BACKUP_DEST` are given to the hooks and the command. Post hooks also get
+    /// `XDBM_BACKUP_STATUS` and `XDBM_BACKUP_LOG`.
+    Run {
+        /// Name of the backup config.
+        #[arg(add = ArgValueCandidates::new(backup_name_completer_local))]
+        name: String,
+        /// Run the backup even if the destination doesn't have enough free space.
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Set the policy to prune old logs of the backup automatically.
+    /// Logs satisfying any of the conditions, the latest log and the latest successful one are kept.
+    /// Give no option to keep all logs.
+    Retention {
+        /// Name of the backup config.
+        #[arg(add = ArgValueCandidates::new(backup_name_completer_local))]
+        name: String,
+        /// Keep the last N logs.
+        #[arg(long)]
+        keep_last: Option<usize>,
+        /// Keep logs younger than the days.
+        #[arg(long)]
+        max_age_days: Option<u64>,
+    },
+    /// Set how often the backup should run.
+    /// Used by `schedule export` and `daemon`.
+    Schedule {
+        /// Name of the backup config.
+        #[arg(add = ArgValueCandidates::new(backup_name_completer_local))]
+        name: String,
+        /// hourly, daily, weekly, monthly, <N>h or <N>d. Remove the schedule if not given.
+        schedule: Option<backups::schedule::Schedule>,
+    },
+    /// Prune old logs and compact them into monthly summaries.
+    /// Configured retention is used if no option is given.
+    PruneLogs {
+        /// Name of the backup config. All backups on this device if not given.
+        #[arg(add = ArgValueCandidates::new(backup_name_completer_local))]
+        name: Option<String>,
+        /// Keep the last N logs.
+        #[arg(long)]
+        keep_last: Option<usize>,
+        /// Keep logs younger than the days.
+        #[arg(long)]
+        max_age_days: Option<u64>,
+    },
+    /// Check that the destination has enough free space for the backup.
+    /// Exits with failure if it doesn't.
+    Preflight {
+        /// Name of the backup config.
+        #[arg(add = ArgValueCandidates::new(backup_name_completer_local))]
+        name: String,
     },
 }
 
@@ -223,12 +608,78 @@ pub(crate) enum BackupAddCommands {
         #[arg(default_value = "")]
         note: String,
     },
+    /// Shell command executed by `backup run`.
+    Shell {
+        name: String,
+        /// Command line passed to the shell.
+        command: String,
+        #[arg(default_value = "")]
+        note: String,
+    },
+    /// `rclone copy` (or `rclone sync`) executed by `backup run`.
+    Rclone {
+        #[arg(default_value = "rclone")]
+        name: String,
+        /// Use `rclone sync`, which deletes files only in the destination.
+        #[arg(long)]
+        sync: bool,
+        /// Additional argument to rclone. Can be given multiple times.
+        #[arg(long = "arg", allow_hyphen_values = true)]
+        args: Vec<String>,
+        #[arg(default_value = "")]
+        note: String,
+    },
+}
+
+/// Find the value of `--config-dir` from command line `args`.
+/// Only options before the subcommand are looked up,
+/// since `-c` may have other meanings in subcommands.
+/// `args` can be either normal one or the one for the dynamic completion (`xdbm -- xdbm ...`).
+fn config_dir_from_args(args: impl IntoIterator<Item = OsString>) -> Option<PathBuf> {
+    let mut args = args.into_iter().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "--") {
+        args.nth(1);
+    }
+    let mut found = None;
+    while let Some(arg) = args.next() {
+        let Some(arg_str) = arg.to_str() else {
+            break;
+        };
+        if !arg_str.starts_with('-') {
+            // subcommand
+            break;
+        }
+        if arg_str == "-c" || arg_str == "--config-dir" {
+            found = args.next().map(PathBuf::from);
+        } else if let Some(value) = arg_str.strip_prefix("--config-dir=") {
+            found = Some(PathBuf::from(value));
+        } else if let Some(value) = arg_str.strip_prefix("-c")
+            && !arg_str.starts_with("--")
+        {
+            found = Some(PathBuf::from(value.strip_prefix('=').unwrap_or(value)));
+        }
+    }
+    found
+}
+
+/// Config dir for completers.
+/// Resolved in the same order as [`Cli::config_dir`]:
+/// already typed `--config-dir`, [`CONFIG_DIR_ENV`], then the default one.
+fn completion_config_dir() -> anyhow::Result<PathBuf> {
+    if let Some(config_dir) = config_dir_from_args(std::env::args_os()) {
+        return crate::util::expand_tilde(config_dir);
+    }
+    if let Some(config_dir) = std::env::var_os(CONFIG_DIR_ENV)
+        && !config_dir.is_empty()
+    {
+        return Ok(PathBuf::from(config_dir));
+    }
+    crate::default_config_dir()
 }
 
 fn storage_name_completer() -> Vec<CompletionCandidate> {
     let mut completions = vec![];
-    // TODO: support custom config dir with env var
-    let config_dir = match crate::default_config_dir() {
+    let config_dir = match completion_config_dir() {
         Ok(p) => p,
         Err(e) => {
             eprintln!("failed to get config dir: {e}");
@@ -248,7 +699,7 @@ fn storage_name_completer() -> Vec<CompletionCandidate> {
 }
 
 fn device_name_completer() -> Vec<CompletionCandidate> {
-    let config_dir = match crate::default_config_dir() {
+    let config_dir = match completion_config_dir() {
         Ok(p) => p,
         Err(e) => {
             eprintln!("failed to get config dir: {e}");
@@ -268,8 +719,26 @@ fn device_name_completer() -> Vec<CompletionCandidate> {
         .collect()
 }
 
+fn drive_name_completer() -> Vec<CompletionCandidate> {
+    let config_dir = match completion_config_dir() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("failed to get config dir: {e}");
+            return vec![];
+        }
+    };
+    let drives = match drives::Drives::read(&config_dir) {
+        Ok(drives) => drives,
+        Err(e) => {
+            eprintln!("{e}");
+            return vec![];
+        }
+    };
+    drives.list.keys().map(CompletionCandidate::new).collect()
+}
+
 fn backup_name_completer_local() -> Vec<CompletionCandidate> {
-    let config_dir = match crate::default_config_dir() {
+    let config_dir = match completion_config_dir() {
         Ok(p) => p,
         Err(e) => {
             eprintln!("failed to get config dir: {e}");
@@ -292,3 +761,40 @@ fn backup_name_completer_local() -> Vec<CompletionCandidate> {
     };
     backups.list.keys().map(CompletionCandidate::new).collect()
 }
+
+#[cfg(test)]
+mod test {
+    use std::{ffi::OsString, path::PathBuf};
+
+    use super::config_dir_from_args;
+
+    fn parse(args: &[&str]) -> Option<PathBuf> {
+        config_dir_from_args(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn config_dir_in_args() {
+        assert_eq!(parse(&["xdbm", "--", "xdbm", "storage", "bind", ""]), None);
+        assert_eq!(
+            parse(&["xdbm", "--", "xdbm", "-c", "/tmp/xdbm", "storage", ""]),
+            Some(PathBuf::from("/tmp/xdbm"))
+        );
+        assert_eq!(
+            parse(&["xdbm", "--", "xdbm", "--config-dir=/tmp/xdbm", "path"]),
+            Some(PathBuf::from("/tmp/xdbm"))
+        );
+        assert_eq!(
+            parse(&["xdbm", "--", "xdbm", "-c/tmp/xdbm", "backup", "done"]),
+            Some(PathBuf::from("/tmp/xdbm"))
+        );
+        assert_eq!(
+            parse(&["xdbm", "-v", "-c", "/tmp/xdbm", "storage", "list"]),
+            Some(PathBuf::from("/tmp/xdbm"))
+        );
+        // options of subcommands are not the config dir
+        assert_eq!(
+            parse(&["xdbm", "storage", "add", "online", "-c", "100"]),
+            None
+        );
+    }
+}
@@ -2,6 +2,8 @@ use std::{
     collections::BTreeMap,
     io::{self, stdout, Write},
     path::{Path, PathBuf},
+    process,
+    time::Instant,
 };
 
 use anyhow::{anyhow, Context, Ok, Result};
@@ -12,21 +14,31 @@ use git2::Repository;
 use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    add_and_commit,
+    add_and_commit_paths,
     backups::{
-        self, Backup, BackupCommand, BackupCommandExt, BackupLog, BackupResult, BackupTarget,
-        Backups, ExternallyInvoked,
+        retention::LogRetention, schedule::Schedule, Backup, BackupCommand, BackupCommandExt,
+        BackupLog, BackupResult, BackupTarget, Backups, ExternallyInvoked, RcloneCommand,
+        RcloneMode, ShellCommand,
     },
     cmd_args::BackupAddCommands,
     devices::{self, Device},
-    storages::{StorageExt, Storages},
+    hooks::{self, Hooks},
+    notify::{self, Notification},
+    preflight,
+    storages::{rclone_remote, Storage, StorageExt, Storages},
     util,
 };
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn cmd_backup_add(
     name: String,
     src: PathBuf,
     dest: PathBuf,
+    dest_storage: Option<String>,
+    force: bool,
+    hooks: Hooks,
+    exit_codes: BTreeMap<u64, BackupResult>,
+    schedule: Option<Schedule>,
     cmd: BackupAddCommands,
     repo: Repository,
     config_dir: &Path,
@@ -34,20 +46,44 @@ pub(crate) fn cmd_backup_add(
 ) -> Result<()> {
     trace!("Canonicalize path: {:?}", src);
     let src = canonicalize(util::expand_tilde(src)?)?;
-    trace!("Canonicalize path: {:?}", dest);
-    let dest = canonicalize(util::expand_tilde(dest)?)?;
+    // relative to `dest_storage` if given
+    let dest = match dest_storage {
+        Some(_) => dest,
+        None => {
+            trace!("Canonicalize path: {:?}", dest);
+            canonicalize(util::expand_tilde(dest)?)?
+        }
+    };
     let device = devices::get_device(config_dir)?;
-    let new_backup = new_backup(name, src, dest, cmd, &device, storages)?;
+    let new_backup = new_backup(
+        name,
+        src.clone(),
+        dest,
+        dest_storage,
+        cmd,
+        &device,
+        storages,
+    )?
+    .with_hooks(hooks)
+    .with_exit_codes(exit_codes)
+    .with_schedule(schedule);
     let new_backup_name = new_backup.name().clone();
+    let dest_storage = storages
+        .get(&new_backup.destination().storage)
+        .context("Couldn't find the destination storage")?;
+    let dest = new_backup.destination().path(storages, &device);
+    let capacity =
+        preflight::check_capacity(&src, dest.as_deref(), dest_storage, storages, &device)?;
+    report_capacity(&capacity, force)?;
     let mut backups = Backups::read(config_dir, &device)?;
     println!("Backup config:");
     serde_yaml::to_writer(stdout(), &new_backup)?;
     backups.add(new_backup)?;
-    backups.write(config_dir, &device)?;
+    let paths = backups.write(config_dir, &device)?;
 
-    add_and_commit(
+    add_and_commit_paths(
         &repo,
-        &backups::backups_file(&device),
+        &paths,
         &format!("Add new backup: {}", new_backup_name),
     )?;
 
@@ -56,10 +92,41 @@ pub(crate) fn cmd_backup_add(
     Ok(())
 }
 
+/// Print the result of [`preflight::check_capacity`].
+/// Returns error if the destination doesn't have enough space, unless `force` is `true`.
+fn report_capacity(capacity: &preflight::Capacity, force: bool) -> Result<()> {
+    match capacity.check {
+        preflight::CapacityCheck::Sufficient { .. } => {
+            info!("Capacity check passed: {}", capacity)
+        }
+        preflight::CapacityCheck::Tight { .. } | preflight::CapacityCheck::Unknown { .. } => {
+            eprintln!(
+                "{} {}",
+                console::style("Warning:").yellow().bold(),
+                capacity
+            )
+        }
+        preflight::CapacityCheck::Insufficient { .. } => {
+            if force {
+                eprintln!("{} {}", console::style("Warning:").red().bold(), capacity)
+            } else {
+                return Err(anyhow!(
+                    "Not enough free space on the destination: {}",
+                    capacity
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `dest` is a path relative to `dest_storage` if it is given,
+/// which is needed for storages without local paths like rclone remotes.
 fn new_backup(
     name: String,
     src: PathBuf,
     dest: PathBuf,
+    dest_storage: Option<String>,
     cmd: BackupAddCommands,
     device: &Device,
     storages: &Storages,
@@ -69,32 +136,86 @@ fn new_backup(
             "Coundn't find parent storage for src directory {}",
             src.display()
         ))?;
-    let (dest_parent, dest_diff) =
-        util::min_parent_storage(&dest, storages, device).context(format!(
-            "Couldn't find parent storage for dest directory: {}",
-            dest.display()
-        ))?;
     let src_target = BackupTarget::new(src_parent.name().to_string(), src_diff);
     trace!("Backup source target: {:?}", src_target);
-    let dest_target = BackupTarget::new(dest_parent.name().to_string(), dest_diff);
+    let dest_target = match dest_storage {
+        Some(dest_storage) => {
+            if storages.get(&dest_storage).is_none() {
+                return Err(anyhow!("No storage has name {}", dest_storage));
+            }
+            if dest.is_absolute() {
+                return Err(anyhow!(
+                    "Destination {} must be relative to the storage {}",
+                    dest.display(),
+                    dest_storage
+                ));
+            }
+            BackupTarget::new(dest_storage, dest)
+        }
+        None => {
+            let (dest_parent, dest_diff) = util::min_parent_storage(&dest, storages, device)
+                .context(format!(
+                    "Couldn't find parent storage for dest directory: {}",
+                    dest.display()
+                ))?;
+            BackupTarget::new(dest_parent.name().to_string(), dest_diff)
+        }
+    }?;
     trace!("Backup destination target: {:?}", dest_target);
 
     let command: BackupCommand = match cmd {
         BackupAddCommands::External { name, note } => {
             BackupCommand::ExternallyInvoked(ExternallyInvoked::new(name, note))
         }
+        BackupAddCommands::Shell {
+            name,
+            command,
+            note,
+        } => BackupCommand::Shell(ShellCommand::new(name, command, note)),
+        BackupAddCommands::Rclone {
+            name,
+            sync,
+            args,
+            note,
+        } => {
+            let mode = if sync {
+                RcloneMode::Sync
+            } else {
+                RcloneMode::Copy
+            };
+            BackupCommand::Rclone(RcloneCommand::new(name, mode, args, note))
+        }
     };
     trace!("Backup command: {:?}", command);
+    if let BackupCommand::Rclone(_) = command {
+        check_writable_destination(&dest_target, storages)?;
+    }
 
     Ok(Backup::new(
         name,
         device.name(),
         src_target?,
-        dest_target?,
+        dest_target,
         command,
     ))
 }
 
+/// Backup commands run by xdbm can't write into archives and offline media,
+/// as their locations are not paths which the commands accept.
+fn check_writable_destination(dest: &BackupTarget, storages: &Storages) -> Result<()> {
+    let kind = match storages.get(&dest.storage) {
+        Some(Storage::Archive(_)) => "an archive",
+        Some(Storage::Offline(_)) => "offline media",
+        _ => return Ok(()),
+    };
+    Err(anyhow!(
+        "Destination storage {} is {}, which backup commands can't write into. \
+        Use an external backup and record it by `backup done`.",
+        dest.storage,
+        kind
+    ))
+}
+
 pub fn cmd_backup_list(
     src_storage: Option<String>,
     dest_storage: Option<String>,
@@ -185,9 +306,9 @@ fn write_backups_list(
         src_storage_width = src_storage_width.max(backup.source().storage.width());
         let dest = backup
             .destination()
-            .path(storages, device)
+            .location(storages, device)
             .context("Couldn't get path for destination")?;
-        dest_width = dest_width.max(format!("{}", dest.display()).width());
+        dest_width = dest_width.max(dest.width());
         dest_storage_width = dest_storage_width.max(backup.destination().storage.width());
         let cmd_name = backup.command().name();
         cmd_name_width = cmd_name_width.max(cmd_name.width());
@@ -204,7 +325,7 @@ fn write_backups_list(
             .context("Couldn't get path for source")?;
         let dest = backup
             .destination()
-            .path(storages, device)
+            .location(storages, device)
             .context("Couldn't get path for destination")?;
         let cmd_name = backup.command().name();
         let (last_backup_elapsed, style_on_time_elapsed) = match backup.last_backup() {
@@ -219,10 +340,14 @@ fn write_backups_list(
                 (style.apply_to("---".to_string()), style)
             }
         };
+        let last_status = match backup.last_backup() {
+            Some(log) => log.status().style().apply_to(log.status().to_string()),
+            None => Style::new().apply_to("".to_string()),
+        };
         if !longprint {
             writeln!(
                 writer,
-                "{name:<name_width$} [{dev:<dev_width$}] {src:<src_storage_width$} → {dest:<dest_storage_width$} {last_backup_elapsed}",
+                "{name:<name_width$} [{dev:<dev_width$}] {src:<src_storage_width$} → {dest:<dest_storage_width$} {last_backup_elapsed} {last_status}",
                 name = style_on_time_elapsed.apply_to(backup.name()),
                 dev = console::style(dev).blue(),
                 src = backup.source().storage,
@@ -236,7 +361,18 @@ fn write_backups_list(
                 name = style_on_time_elapsed.bold().apply_to(backup.name()),
             )?;
             let last_backup_date = match backup.last_backup() {
-                Some(date) => date.datetime.format("%Y-%m-%d %T").to_string(),
+                Some(log) => {
+                    let exit_code = match log.exit_code() {
+                        Some(code) => format!(" (exit code {})", code),
+                        None => "".to_string(),
+                    };
+                    format!(
+                        "{} {}{}",
+                        log.datetime.format("%Y-%m-%d %T"),
+                        last_status,
+                        exit_code
+                    )
+                }
                 None => "never".to_string(),
             };
             let cmd_note = backup.command().note();
@@ -250,7 +386,6 @@ fn write_backups_list(
                 writer,
                 "{s_dest} {dest}",
                 s_dest = console::style("dest:").italic().bright().black(),
-                dest = dest.display()
             )?;
             writeln!(
                 writer,
@@ -258,6 +393,29 @@ fn write_backups_list(
                 s_last = console::style("last:").italic().bright().black(),
                 last = last_backup_date,
             )?;
+            if let Some(log) = backup.last_backup()
+                && !log.log().is_empty()
+            {
+                writeln!(
+                    writer,
+                    "{s_log} {log}",
+                    s_log = console::style("log :").italic().bright().black(),
+                    log = log.log().lines().last().unwrap_or(""),
+                )?;
+            }
+            let (n_runs, n_ok_runs) = backup.count_runs();
+            writeln!(
+                writer,
+                "{s_runs} {n_ok_runs}/{n_runs} succeeded",
+                s_runs = console::style("runs:").italic().bright().black(),
+            )?;
+            if let Some(schedule) = backup.schedule() {
+                writeln!(
+                    writer,
+                    "{s_when} {schedule}",
+                    s_when = console::style("when:").italic().bright().black(),
+                )?;
+            }
             writeln!(
                 writer,
                 "{s_cmd} {cmd_name}({note})",
@@ -271,10 +429,14 @@ fn write_backups_list(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn cmd_backup_done(
     name: String,
     exit_status: u64,
+    status: Option<BackupResult>,
     log: Option<String>,
+    duration: Option<u64>,
+    bytes: Option<u64>,
     repo: Repository,
     config_dir: &Path,
 ) -> Result<()> {
@@ -285,20 +447,301 @@ pub fn cmd_backup_done(
         .context(format!("Failed to get backup with name {}", name))?;
     trace!("Got backup: {:?}", backup);
     let backup_name = backup.name().clone();
-    let status = BackupResult::from_exit_code(exit_status);
-    let new_log = BackupLog::new_with_current_time(status, log.unwrap_or("".to_string()));
+    let status = status.unwrap_or_else(|| {
+        BackupResult::from_exit_code(exit_status, backup.command().exit_codes())
+    });
+    let new_log =
+        BackupLog::new_with_current_time(status, Some(exit_status), log.unwrap_or("".to_string()))
+            .with_duration(duration)
+            .with_bytes(bytes);
     trace!("New backup log: {:?}", new_log);
+    let notification = Notification::from_log(&backup_name, &device.name(), &new_log);
     backup.add_log(new_log);
     trace!("Added");
-    backups.write(config_dir, &device)?;
-    add_and_commit(
+    let paths = backups.write(config_dir, &device)?;
+    add_and_commit_paths(&repo, &paths, &format!("Done backup: {}", backup_name))?;
+    notify::notify_result(config_dir, &notification);
+    Ok(())
+}
+
+pub fn cmd_backup_retention(
+    name: String,
+    retention: LogRetention,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let mut backups = Backups::read(config_dir, &device)?;
+    let backup = backups
+        .get_mut(&name)
+        .context(format!("Failed to get backup with name {}", name))?;
+    backup.set_retention(retention.clone());
+    let n_pruned = backup.prune_logs(&retention);
+    println!("{}: pruned {} logs", backup.name(), n_pruned);
+    let backup_name = backup.name().clone();
+    let paths = backups.write(config_dir, &device)?;
+    add_and_commit_paths(
+        &repo,
+        &paths,
+        &format!("Set log retention of backup: {}", backup_name),
+    )?;
+    Ok(())
+}
+
+pub fn cmd_backup_schedule(
+    name: String,
+    schedule: Option<Schedule>,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let mut backups = Backups::read(config_dir, &device)?;
+    let backup = backups
+        .get_mut(&name)
+        .context(format!("Failed to get backup with name {}", name))?;
+    backup.set_schedule(schedule);
+    match schedule {
+        Some(schedule) => println!("{}: scheduled {}", backup.name(), schedule),
+        None => println!("{}: removed schedule", backup.name()),
+    }
+    let backup_name = backup.name().clone();
+    let paths = backups.write(config_dir, &device)?;
+    add_and_commit_paths(
         &repo,
-        &backups::backups_file(&device),
-        &format!("Done backup: {}", backup_name),
+        &paths,
+        &format!("Set schedule of backup: {}", backup_name),
     )?;
     Ok(())
 }
 
+/// Prune logs with `retention`, or the configured one if `retention` is empty.
+pub fn cmd_backup_prune_logs(
+    name: Option<String>,
+    retention: LogRetention,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let mut backups = Backups::read(config_dir, &device)?;
+    if let Some(name) = &name
+        && backups.get(name).is_none()
+    {
+        return Err(anyhow!("Failed to get backup with name {}", name));
+    }
+    let mut n_pruned_all = 0;
+    for backup in backups.list.values_mut() {
+        if name.as_ref().is_some_and(|name| name != backup.name()) {
+            continue;
+        }
+        let retention = if retention.is_empty() {
+            backup.retention().clone()
+        } else {
+            retention.clone()
+        };
+        if retention.is_empty() {
+            println!("{}: no retention configured", backup.name());
+            continue;
+        }
+        let n_pruned = backup.prune_logs(&retention);
+        println!("{}: pruned {} logs", backup.name(), n_pruned);
+        n_pruned_all += n_pruned;
+    }
+    if n_pruned_all == 0 {
+        return Ok(());
+    }
+    let paths = backups.write(config_dir, &device)?;
+    add_and_commit_paths(
+        &repo,
+        &paths,
+        &format!("Prune {} logs of backups", n_pruned_all),
+    )?;
+    Ok(())
+}
+
+/// Number of lines of the command output kept in [`BackupLog`].
+const RUN_LOG_LINES: usize = 20;
+
+pub fn cmd_backup_run(
+    name: String,
+    force: bool,
+    repo: Repository,
+    config_dir: &Path,
+    storages: &Storages,
+) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let mut backups = Backups::read(config_dir, &device)?;
+    let global_hooks = Hooks::read_global(config_dir)?;
+    let backup = backups
+        .get_mut(&name)
+        .context(format!("Failed to get backup with name {}", name))?;
+    let exit_codes = backup.command().exit_codes().clone();
+    let src = backup
+        .source()
+        .path(storages, &device)
+        .context("Source of the backup is not available on this device")?;
+    // remote destinations don't have local paths
+    let dest_path = backup.destination().path(storages, &device);
+    let dest = backup
+        .destination()
+        .location(storages, &device)
+        .context("Destination of the backup is not available on this device")?;
+    let command = match backup.command() {
+        BackupCommand::Shell(cmd) => hooks::shell_command(&cmd.command),
+        BackupCommand::Rclone(cmd) => {
+            let mut command = rclone_remote::rclone_command();
+            command.args(cmd.rclone_args(&src.display().to_string(), &dest));
+            command
+        }
+        BackupCommand::ExternallyInvoked(_) => {
+            return Err(anyhow!(
+                "Backup {} is invoked externally. Use `backup done` to record it.",
+                name
+            ));
+        }
+    };
+    check_writable_destination(backup.destination(), storages)?;
+    let mut envs = vec![
+        ("XDBM_BACKUP_NAME", backup.name().clone()),
+        ("XDBM_DEVICE", device.name()),
+        ("XDBM_BACKUP_SRC", src.display().to_string()),
+        ("XDBM_BACKUP_DEST", dest),
+    ];
+
+    let pre_hooks: Vec<String> = global_hooks
+        .pre
+        .iter()
+        .chain(backup.hooks().pre.iter())
+        .cloned()
+        .collect();
+    let mut duration = None;
+    let mut bytes = None;
+    let (status, exit_code, log) = match hooks::run_hooks(&pre_hooks, &envs) {
+        Err(e) => {
+            eprintln!(
+                "{} {:#}",
+                console::style("Pre-hook failed:").red().bold(),
+                e
+            );
+            (
+                BackupResult::Failure,
+                None,
+                format!("pre-hook failed: {:#}", e),
+            )
+        }
+        Result::Ok(()) => {
+            let dest_storage = storages
+                .get(&backup.destination().storage)
+                .context("Couldn't find the destination storage")?;
+            let capacity = preflight::check_capacity(
+                &src,
+                dest_path.as_deref(),
+                dest_storage,
+                storages,
+                &device,
+            )
+            .and_then(|capacity| report_capacity(&capacity, force).map(|()| capacity));
+            match capacity {
+                Err(e) => (BackupResult::Failure, None, format!("{:#}", e)),
+                Result::Ok(capacity) => {
+                    let started = Instant::now();
+                    match run_backup_command(command, &exit_codes, &envs) {
+                        Err(e) => {
+                            eprintln!(
+                                "{} {:#}",
+                                console::style("Backup command failed:").red().bold(),
+                                e
+                            );
+                            (BackupResult::Failure, None, format!("{:#}", e))
+                        }
+                        Result::Ok(result) => {
+                            duration = Some(started.elapsed().as_secs());
+                            bytes = Some(capacity.src_size());
+                            result
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    envs.push(("XDBM_BACKUP_STATUS", status.to_string()));
+    if let Some(code) = exit_code {
+        envs.push(("XDBM_BACKUP_EXIT_CODE", code.to_string()));
+    }
+    envs.push(("XDBM_BACKUP_LOG", log.clone()));
+    let post_hooks: Vec<String> = backup
+        .hooks()
+        .post
+        .iter()
+        .chain(global_hooks.post.iter())
+        .cloned()
+        .collect();
+    if let Err(e) = hooks::run_hooks(&post_hooks, &envs) {
+        eprintln!(
+            "{} {:#}",
+            console::style("Post-hook failed:").red().bold(),
+            e
+        );
+    }
+
+    let backup_name = backup.name().clone();
+    let new_log = BackupLog::new_with_current_time(status, exit_code, log)
+        .with_duration(duration)
+        .with_bytes(bytes);
+    trace!("New backup log: {:?}", new_log);
+    let notification = Notification::from_log(&backup_name, &device.name(), &new_log);
+    backup.add_log(new_log);
+    let paths = backups.write(config_dir, &device)?;
+    add_and_commit_paths(&repo, &paths, &format!("Run backup: {}", backup_name))?;
+    notify::notify_result(config_dir, &notification);
+    if !status.is_ok() {
+        return Err(anyhow!("Backup {} finished with {}", backup_name, status));
+    }
+    println!("Backup {} finished with {}", backup_name, status);
+    Ok(())
+}
+
+/// Execute `command` and get its result, exit code and the tail of the output.
+fn run_backup_command(
+    mut command: process::Command,
+    exit_codes: &BTreeMap<u64, BackupResult>,
+    envs: &[(&str, String)],
+) -> Result<(BackupResult, Option<u64>, String)> {
+    info!("Running backup command: {:?}", command);
+    let output = command
+        .envs(envs.iter().map(|(k, v)| (k, v)))
+        .output()
+        .context(format!("Failed to execute {:?}", command))?;
+    io::stdout().write_all(&output.stdout)?;
+    io::stderr().write_all(&output.stderr)?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lines: Vec<&str> = combined.lines().collect();
+    let log = lines[lines.len().saturating_sub(RUN_LOG_LINES)..].join("\n");
+    let exit_code = output.status.code().map(|code| code as u64);
+    let status = match exit_code {
+        Some(code) => BackupResult::from_exit_code(code, exit_codes),
+        // terminated by signal
+        None => BackupResult::Cancelled,
+    };
+    Ok((status, exit_code, log))
+}
+
+pub fn cmd_backup_preflight(name: String, config_dir: &Path, storages: &Storages) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let backups = Backups::read(config_dir, &device)?;
+    let backup = backups
+        .get(&name)
+        .context(format!("Failed to get backup with name {}", name))?;
+    let capacity = preflight::check_backup_capacity(backup, storages, &device)?;
+    report_capacity(&capacity, false)?;
+    println!("{}: {}", backup.name(), capacity);
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use std::path::{Component, PathBuf};
@@ -308,7 +751,12 @@ mod test {
     use crate::{
         cmd_args::BackupAddCommands,
         devices::Device,
-        storages::{online_storage::OnlineStorage, Storage, Storages},
+        storages::{
+            offline_media::{MediaKind, OfflineMedia},
+            online_storage::OnlineStorage,
+            rclone_remote::RcloneRemote,
+            Storage, Storages,
+        },
     };
 
     use super::new_backup;
@@ -356,6 +804,7 @@ mod test {
                 .join("mnt")
                 .join("sample")
                 .join("tmp"),
+            None,
             cmd,
             &device,
             &storages,
@@ -364,6 +813,58 @@ mod test {
         assert_eq!(backup.source().path, vec!["docs"]);
         assert!(backup.destination().storage == "online");
         assert!(backup.destination().path == vec!["tmp"]);
+
+        storages.add(Storage::Rclone(RcloneRemote::new(
+            "gdrive".to_string(),
+            "gdrive".to_string(),
+        )))?;
+        let cmd = BackupAddCommands::Rclone {
+            name: "rclone".to_string(),
+            sync: false,
+            args: vec![],
+            note: "".to_string(),
+        };
+        let backup = new_backup(
+            "to cloud".to_string(),
+            PathBuf::from("/mnt/sample/docs"),
+            PathBuf::from("backup/docs"),
+            Some("gdrive".to_string()),
+            cmd,
+            &device,
+            &storages,
+        )?;
+        assert_eq!(backup.destination().storage, "gdrive");
+        assert_eq!(backup.destination().path, vec!["backup", "docs"]);
+        assert_eq!(
+            backup.destination().location(&storages, &device),
+            Some("gdrive:backup/docs".to_string())
+        );
+
+        // offline media can't be written by rclone
+        storages.add(Storage::Offline(OfflineMedia::new(
+            "disc".to_string(),
+            MediaKind::Bluray,
+            "BD-001".to_string(),
+            "home safe".to_string(),
+            chrono::NaiveDate::from_ymd_opt(2026, 1, 3).unwrap(),
+            None,
+        )))?;
+        let cmd = BackupAddCommands::Rclone {
+            name: "rclone".to_string(),
+            sync: false,
+            args: vec![],
+            note: "".to_string(),
+        };
+        let result = new_backup(
+            "to disc".to_string(),
+            PathBuf::from("/mnt/sample/docs"),
+            PathBuf::from("docs"),
+            Some("disc".to_string()),
+            cmd,
+            &device,
+            &storages,
+        );
+        assert!(result.is_err());
         Ok(())
     }
 }
@@ -5,6 +5,7 @@ use anyhow::{anyhow, Context, Result};
 use crate::{
     backups::Backups,
     devices,
+    drives::Drives,
     storages::{Storage, StorageExt, Storages},
 };
 
@@ -20,14 +21,56 @@ pub(crate) fn cmd_check(config_dir: &Path) -> Result<()> {
     let storages = Storages::read(config_dir)?;
     info!("Storages: {:?}", storages);
     if !(storages.list.iter().all(|(_name, storage)| match storage {
-        Storage::SubDirectory(storage) => storage.parent(&storages).is_some(),
+        Storage::SubDirectory(_) | Storage::Archive(_) => storage.parent(&storages).is_some(),
         _ => true,
     })) {
         return Err(anyhow!(
-            "Some SubDirectory doesn't have its parent in the storages list."
+            "Some SubDirectory or Archive doesn't have its parent in the storages list."
         ));
     };
-    info!("All SubDirectory's parent exists.");
+    info!("All SubDirectory's and Archive's parent exists.");
+    let mut n_warnings = 0;
+    let drives = Drives::read(config_dir)?;
+    for storage in storages.list.values() {
+        if let Storage::Physical(partition) = storage
+            && let Some(drive) = &partition.drive
+            && drives.get(drive).is_none()
+        {
+            return Err(anyhow!(
+                "The drive {} of storage {} doesn't exist in drives.",
+                drive,
+                partition.name()
+            ));
+        }
+    }
+    for drive in drives.list.values() {
+        if let Some(member) = drive.members.iter().find(|m| drives.get(m).is_none()) {
+            return Err(anyhow!(
+                "The member {} of drive {} doesn't exist in drives.",
+                member,
+                drive.name()
+            ));
+        }
+    }
+    // archives can't be browsed, but at least the file or repository should be there
+    for storage in storages.list.values() {
+        if let Storage::Archive(archive) = storage
+            && archive
+                .parent(&storages)
+                .and_then(|parent| parent.mount_path(&device))
+                .is_some_and(|parent_path| parent_path.exists())
+            && let Some(path) = archive.path(&storages, &device)
+            && !path.exists()
+        {
+            println!(
+                "{} Archive {} is not found at {}",
+                console::style("Warning:").yellow().bold(),
+                archive.name(),
+                path.display()
+            );
+            n_warnings += 1;
+        }
+    }
 
     for device in &devices {
         let backups = Backups::read(config_dir, device)?;
@@ -53,8 +96,32 @@ pub(crate) fn cmd_check(config_dir: &Path) -> Result<()> {
                     &backup.name()
                 ));
             }
+            for (role, target) in [
+                ("source", backup.source()),
+                ("destination", backup.destination()),
+            ] {
+                if let Some(drive) = storages
+                    .get(&target.storage)
+                    .and_then(|storage| storages.partition(storage))
+                    && !drive.status.is_active()
+                {
+                    println!(
+                        "{} The {} of backup {} is on the {} drive {}",
+                        console::style("Warning:").yellow().bold(),
+                        role,
+                        backup.name(),
+                        drive.status,
+                        drive.name()
+                    );
+                    n_warnings += 1;
+                }
+            }
         }
     }
-    println!("All check passed");
+    match n_warnings {
+        0 => println!("All check passed"),
+        1 => println!("Check passed with 1 warning"),
+        n => println!("Check passed with {} warnings", n),
+    }
     Ok(())
 }
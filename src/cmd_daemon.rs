@@ -0,0 +1,313 @@
+//! Daemon subcommand.
+//! Periodically runs scheduled backups of the current device when they are due.
+
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeDelta};
+use git2::Repository;
+use sysinfo::Disks;
+
+use crate::{
+    backups::{Backup, BackupCommand, Backups},
+    cmd_backup, cmd_sync,
+    devices::{self, Device},
+    settings::Settings,
+    storages::{Storage, StorageExt, Storages, usage},
+};
+
+/// Whether a scheduled backup should be run now.
+#[derive(Debug, PartialEq)]
+enum Due {
+    /// No schedule or can't be run by xdbm.
+    NotScheduled,
+    /// Next run is at the time.
+    NotYet(DateTime<Local>),
+    /// The storage of the source or destination is not mounted.
+    Unavailable(String),
+    /// Should be run now.
+    Ready,
+}
+
+/// Failed backups are retried after this number of checks, or by the schedule if it is shorter.
+const RETRY_CHECKS: i32 = 12;
+
+/// `retry` is the time to wait before running a failed backup again.
+fn check_due(
+    backup: &Backup,
+    storages: &Storages,
+    device: &Device,
+    now: DateTime<Local>,
+    retry: TimeDelta,
+) -> Due {
+    let Some(schedule) = backup.schedule() else {
+        return Due::NotScheduled;
+    };
+    if matches!(backup.command(), BackupCommand::ExternallyInvoked(_)) {
+        return Due::NotScheduled;
+    }
+    let next_scheduled = backup
+        .last_success()
+        .map(|last| last.datetime + schedule.interval());
+    let next_retry = backup
+        .last_backup()
+        .filter(|last| !last.status().is_ok())
+        .map(|last| last.datetime + retry.min(schedule.interval()));
+    if let Some(next) = next_scheduled.max(next_retry)
+        && next > now
+    {
+        return Due::NotYet(next);
+    }
+    for target in [backup.source(), backup.destination()] {
+        let available = match storages.get(&target.storage) {
+            // reachable without mounting
+            Some(Storage::Rclone(_)) => true,
+            Some(storage) => {
+                let path = match storage {
+                    Storage::Archive(archive) => archive.path(storages, device),
+                    _ => target.path(storages, device),
+                };
+                let mounted = match storages.root(storage) {
+                    // the mount point exists even if the drive is not mounted
+                    Some(root @ Storage::Physical(_)) => {
+                        root.mount_path(device).is_some_and(|mount_path| {
+                            let disks = Disks::new_with_refreshed_list();
+                            usage::find_disk(&disks, &mount_path, true).is_some()
+                        })
+                    }
+                    Some(root) => root.is_mounted(device) != Some(false),
+                    None => false,
+                };
+                mounted && path.is_some_and(|path| path.exists())
+            }
+            None => false,
+        };
+        if !available {
+            return Due::Unavailable(target.storage.clone());
+        }
+    }
+    Due::Ready
+}
+
+pub(crate) fn cmd_daemon(
+    interval: u64,
+    once: bool,
+    sync: bool,
+    config_dir: &PathBuf,
+    settings: &Settings,
+) -> Result<()> {
+    loop {
+        // keep running even if the config is temporarily broken, e.g. by a conflicting sync
+        let retry = TimeDelta::seconds(interval as i64) * RETRY_CHECKS;
+        let n_runs = match run_due_backups(config_dir, retry) {
+            Ok(n_runs) => n_runs,
+            Err(e) if once => return Err(e),
+            Err(e) => {
+                eprintln!(
+                    "{} {:#}",
+                    console::style("Failed to run backups:").red().bold(),
+                    e
+                );
+                0
+            }
+        };
+        if sync && n_runs > 0 {
+            let result = cmd_sync::cmd_sync(
+                config_dir,
+                settings.remote.clone(),
+                settings.use_sshagent,
+                settings.ssh_key.clone(),
+                settings.use_libgit2,
+            );
+            if let Err(e) = result {
+                eprintln!("{} {:#}", console::style("Sync failed:").red().bold(), e);
+            }
+        }
+        if once {
+            return Ok(());
+        }
+        trace!("Sleeping {} seconds", interval);
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+/// Run all due backups of this device once. Returns the number of backups run.
+fn run_due_backups(config_dir: &Path, retry: TimeDelta) -> Result<usize> {
+    let device = devices::get_device(config_dir)?;
+    let backups = Backups::read(config_dir, &device)?;
+    let storages = Storages::read(config_dir)?;
+    let now = Local::now();
+    let mut n_runs = 0;
+    for backup in backups.list.values() {
+        match check_due(backup, &storages, &device, now, retry) {
+            Due::NotScheduled => continue,
+            Due::NotYet(next) => {
+                debug!("{} is due at {}", backup.name(), next);
+                continue;
+            }
+            Due::Unavailable(storage) => {
+                println!(
+                    "[{}] {}: skipped, storage {} is not mounted",
+                    now.format("%Y-%m-%d %T"),
+                    backup.name(),
+                    storage
+                );
+                continue;
+            }
+            Due::Ready => {}
+        }
+        println!("[{}] {}: running", now.format("%Y-%m-%d %T"), backup.name());
+        let repo = Repository::open(config_dir).context("Failed to open the repository")?;
+        n_runs += 1;
+        if let Err(e) =
+            cmd_backup::cmd_backup_run(backup.name().clone(), false, repo, config_dir, &storages)
+        {
+            eprintln!("{:#}", e);
+        }
+    }
+    Ok(n_runs)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use chrono::{Local, TimeDelta};
+
+    use crate::{
+        backups::{
+            Backup, BackupCommand, BackupLog, BackupResult, BackupTarget, ShellCommand,
+            schedule::Schedule,
+        },
+        devices::Device,
+        storages::{
+            Storage, Storages, local_info::LocalInfo, online_storage::OnlineStorage,
+            physical_drive_partition::PhysicalDrivePartition,
+        },
+    };
+
+    use super::{Due, check_due};
+
+    #[test]
+    fn due_backups() {
+        let device = Device::new("dev".to_string());
+        let mount = std::env::temp_dir();
+        let mut storages = Storages::new();
+        storages
+            .add(Storage::Online(OnlineStorage::new(
+                "online".to_string(),
+                "provider".to_string(),
+                1_000_000,
+                "alias".to_string(),
+                mount.clone(),
+                &device,
+            )))
+            .unwrap();
+        let new_backup = |dest: &str| {
+            Backup::new(
+                "backup".to_string(),
+                device.name(),
+                BackupTarget::new("online".to_string(), PathBuf::new()).unwrap(),
+                BackupTarget::new(dest.to_string(), PathBuf::new()).unwrap(),
+                BackupCommand::Shell(ShellCommand::new(
+                    "cp".to_string(),
+                    "true".to_string(),
+                    "".to_string(),
+                )),
+            )
+        };
+        let now = Local::now();
+        let retry = TimeDelta::hours(1);
+
+        let mut backup = new_backup("online");
+        assert_eq!(
+            check_due(&backup, &storages, &device, now, retry),
+            Due::NotScheduled
+        );
+        backup.set_schedule(Some(Schedule::Daily));
+        assert_eq!(
+            check_due(&backup, &storages, &device, now, retry),
+            Due::Ready
+        );
+        // just failed, retried later but before the next schedule
+        let failed =
+            BackupLog::new_with_current_time(BackupResult::Failure, Some(1), "".to_string());
+        let next_retry = failed.datetime + retry;
+        backup.add_log(failed);
+        assert_eq!(
+            check_due(&backup, &storages, &device, now, retry),
+            Due::NotYet(next_retry)
+        );
+        assert_eq!(
+            check_due(&backup, &storages, &device, next_retry, retry),
+            Due::Ready
+        );
+        let log = BackupLog::new_with_current_time(BackupResult::Success, Some(0), "".to_string());
+        let next = log.datetime + TimeDelta::days(1);
+        backup.add_log(log);
+        assert_eq!(
+            check_due(&backup, &storages, &device, now, retry),
+            Due::NotYet(next)
+        );
+        assert_eq!(
+            check_due(&backup, &storages, &device, now + TimeDelta::days(2), retry),
+            Due::Ready
+        );
+
+        let mut unavailable = new_backup("missing");
+        unavailable.set_schedule(Some(Schedule::Daily));
+        assert_eq!(
+            check_due(&unavailable, &storages, &device, now, retry),
+            Due::Unavailable("missing".to_string())
+        );
+    }
+
+    #[test]
+    fn unmounted_physical_drive() {
+        let device = Device::new("dev".to_string());
+        // exists, but no drive is mounted here
+        let mount = assert_fs::TempDir::new().unwrap();
+        let mut storages = Storages::new();
+        storages
+            .add(Storage::Online(OnlineStorage::new(
+                "online".to_string(),
+                "provider".to_string(),
+                1_000_000,
+                "alias".to_string(),
+                std::env::temp_dir(),
+                &device,
+            )))
+            .unwrap();
+        storages
+            .add(Storage::Physical(PhysicalDrivePartition::new(
+                "drive".to_string(),
+                "HDD".to_string(),
+                1_000_000,
+                "ext4".to_string(),
+                true,
+                LocalInfo::new("alias".to_string(), mount.path().to_path_buf()),
+                &device,
+            )))
+            .unwrap();
+        let mut backup = Backup::new(
+            "backup".to_string(),
+            device.name(),
+            BackupTarget::new("online".to_string(), PathBuf::new()).unwrap(),
+            BackupTarget::new("drive".to_string(), PathBuf::new()).unwrap(),
+            BackupCommand::Shell(ShellCommand::new(
+                "cp".to_string(),
+                "true".to_string(),
+                "".to_string(),
+            )),
+        );
+        backup.set_schedule(Some(Schedule::Daily));
+        assert_eq!(
+            check_due(&backup, &storages, &device, Local::now(), TimeDelta::zero()),
+            Due::Unavailable("drive".to_string())
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Device subcommands.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::Local;
+use console::Style;
+use git2::Repository;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    add_and_commit, add_and_commit_paths,
+    backups::{self, Backups},
+    devices::{self, DEVICESFILE},
+    git,
+    storages::{STORAGESFILE, StorageExt, Storages},
+    util,
+};
+
+pub(crate) fn cmd_device_list(repo: Repository, config_dir: &Path) -> Result<()> {
+    let devices = devices::get_devices(config_dir)?;
+    let current = devices::get_device(config_dir).ok();
+    let storages = Storages::read(config_dir)?;
+    let last_commits = git::last_device_commits(&repo, &devices)?;
+    let name_width = devices.iter().map(|d| d.name().width()).max().unwrap_or(0);
+    let host_width = devices
+        .iter()
+        .map(|d| d.hostname().width())
+        .max()
+        .unwrap_or(0);
+    let os_width = devices
+        .iter()
+        .map(|d| format!("{} {}", d.os_name(), d.os_version()).width())
+        .max()
+        .unwrap_or(0);
+    for device in &devices {
+        let is_current = current.as_ref().is_some_and(|c| c.name() == device.name());
+        let (mark, style) = if is_current {
+            ("*", Style::new().bold())
+        } else {
+            (" ", Style::new())
+        };
+        let n_backups = if config_dir.join(backups::backups_file(device)).exists() {
+            Backups::read(config_dir, device)?.list.len()
+        } else {
+            0
+        };
+        let last_commit = match last_commits.get(&device.name()) {
+            Some(datetime) => {
+                let elapsed = Local::now() - *datetime;
+                util::duration_style(elapsed).apply_to(format!(
+                    "{} ({} ago)",
+                    datetime.format("%Y-%m-%d"),
+                    util::format_summarized_duration(elapsed)
+                ))
+            }
+            None => Style::new().red().apply_to("---".to_string()),
+        };
+        println!(
+            "{mark} {name:<name_width$} {hostname:<host_width$} {os:<os_width$} {n_backups:>3} backups  last: {last_commit}",
+            name = style.apply_to(device.name()),
+            hostname = device.hostname(),
+            os = format!("{} {}", device.os_name(), device.os_version()),
+        );
+        let bound_storages: Vec<&str> = storages
+            .list
+            .values()
+            .filter(|s| s.local_info(device).is_some())
+            .map(|s| s.name().as_str())
+            .collect();
+        if !bound_storages.is_empty() {
+            println!(
+                "  {:<name_width$} storages: {}",
+                "",
+                bound_storages.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn cmd_device_rename(
+    old_name: String,
+    new_name: String,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    if new_name.is_empty() {
+        return Err(anyhow!("Device name is empty"));
+    }
+    let mut devices = devices::get_devices(config_dir)?;
+    if devices.iter().any(|d| d.name() == new_name) {
+        return Err(anyhow!("Device name {} is already used.", new_name));
+    }
+    let device = devices
+        .iter_mut()
+        .find(|d| d.name() == old_name)
+        .context(format!("Couldn't find device {}", old_name))?;
+    let old_device = device.clone();
+    device.rename(new_name.clone());
+    let new_device = device.clone();
+    let is_current = devices::get_device(config_dir).is_ok_and(|d| d.name() == old_name);
+    devices::write_devices(config_dir, devices)?;
+    let mut paths = vec![PathBuf::from(DEVICESFILE)];
+
+    // local infos of storages
+    let mut storages = Storages::read(config_dir)?;
+    for storage in storages.list.values_mut() {
+        storage.rename_device(&old_name, &new_name);
+    }
+    storages.write(config_dir)?;
+    paths.push(PathBuf::from(STORAGESFILE));
+
+    // backups file and logs
+    if config_dir.join(backups::backups_file(&old_device)).exists() {
+        let mut backups = Backups::read(config_dir, &old_device)?;
+        let old_files = backups
+            .list
+            .keys()
+            .map(|name| backups::logs_file(&old_device, name))
+            .filter(|path| config_dir.join(path).exists())
+            .chain([backups::backups_file(&old_device)])
+            .collect::<Vec<_>>();
+        for backup in backups.list.values_mut() {
+            backup.set_device_name(new_name.clone());
+        }
+        paths.extend(backups.write(config_dir, &new_device)?);
+        paths.extend(remove_files(config_dir, old_files)?);
+    }
+
+    if is_current {
+        devices::write_devname(config_dir, &new_name)?;
+    }
+    add_and_commit_paths(
+        &repo,
+        &paths,
+        &format!("Rename device: {} -> {}", old_name, new_name),
+    )?;
+    println!("Renamed device {} to {}", old_name, new_name);
+    Ok(())
+}
+
+pub(crate) fn cmd_device_remove(
+    name: String,
+    cascade: bool,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    let mut devices = devices::get_devices(config_dir)?;
+    let device = devices
+        .iter()
+        .find(|d| d.name() == name)
+        .context(format!("Couldn't find device {}", name))?
+        .clone();
+    if devices::get_device(config_dir).is_ok_and(|d| d.name() == name) {
+        return Err(anyhow!("Can't remove the current device {}", name));
+    }
+
+    let mut storages = Storages::read(config_dir)?;
+    let bound_storages: Vec<String> = storages
+        .list
+        .values()
+        .filter(|s| s.local_info(&device).is_some())
+        .map(|s| s.name().clone())
+        .collect();
+    let backups = if config_dir.join(backups::backups_file(&device)).exists() {
+        Some(Backups::read(config_dir, &device)?)
+    } else {
+        None
+    };
+    let n_backups = backups.as_ref().map_or(0, |b| b.list.len());
+    if !cascade && (!bound_storages.is_empty() || n_backups > 0) {
+        return Err(anyhow!(
+            "Device {} has {} bound storages ({}) and {} backups. Use --cascade to remove them too.",
+            name,
+            bound_storages.len(),
+            bound_storages.join(", "),
+            n_backups,
+        ));
+    }
+
+    devices.retain(|d| d.name() != name);
+    devices::write_devices(config_dir, devices)?;
+    let mut paths = vec![PathBuf::from(DEVICESFILE)];
+
+    if !bound_storages.is_empty() {
+        for storage in storages.list.values_mut() {
+            storage.unbind_device(&name);
+        }
+        storages.write(config_dir)?;
+        paths.push(PathBuf::from(STORAGESFILE));
+        println!("Unbound storages: {}", bound_storages.join(", "));
+    }
+    if let Some(backups) = backups {
+        let files = backups
+            .list
+            .keys()
+            .map(|backup_name| backups::logs_file(&device, backup_name))
+            .filter(|path| config_dir.join(path).exists())
+            .chain([backups::backups_file(&device)])
+            .collect::<Vec<_>>();
+        paths.extend(remove_files(config_dir, files)?);
+        if n_backups > 0 {
+            println!("Removed {} backups", n_backups);
+        }
+    }
+
+    add_and_commit_paths(&repo, &paths, &format!("Remove device: {}", name))?;
+    println!("Removed device {}", name);
+    Ok(())
+}
+
+pub(crate) fn cmd_device_refresh(repo: Repository, config_dir: &Path) -> Result<()> {
+    let current = devices::get_device(config_dir)?;
+    let mut devices = devices::get_devices(config_dir)?;
+    let device = devices
+        .iter_mut()
+        .find(|d| d.name() == current.name())
+        .context("Couldn't find Device in devices.yml")?;
+    if !device.refresh() {
+        println!("Device {} is up to date.", device.name());
+        return Ok(());
+    }
+    println!(
+        "{}: {} {} {}",
+        device.name(),
+        device.hostname(),
+        device.os_name(),
+        device.os_version()
+    );
+    let name = device.name();
+    devices::write_devices(config_dir, devices)?;
+    add_and_commit(
+        &repo,
+        Path::new(DEVICESFILE),
+        &format!("Refresh device: {}", name),
+    )?;
+    Ok(())
+}
+
+/// Remove `files` relative to `config_dir` and empty log directories.
+/// Returns the removed paths.
+fn remove_files(config_dir: &Path, files: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    let logs_dir = config_dir.join(backups::LOGSDIR);
+    for file in &files {
+        trace!("Removing {}", file.display());
+        fs::remove_file(config_dir.join(file))?;
+        if let Some(parent) = config_dir.join(file).parent()
+            && parent.starts_with(&logs_dir)
+            && parent != logs_dir
+            && fs::read_dir(parent)?.next().is_none()
+        {
+            fs::remove_dir(parent)?;
+        }
+    }
+    Ok(files)
+}
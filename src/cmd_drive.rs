@@ -0,0 +1,85 @@
+//! Drive subcommands.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use console::Style;
+use git2::Repository;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    add_and_commit,
+    drives::{self, Drive, DriveKind, Drives},
+    storages::{Storage, StorageExt, Storages},
+};
+
+pub(crate) fn cmd_drive_add(
+    name: String,
+    kind: DriveKind,
+    members: Vec<String>,
+    notes: String,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    let mut drives = Drives::read(config_dir)?;
+    drives.add(Drive::new(name.clone(), kind, members, notes))?;
+    drives.write(config_dir)?;
+    add_and_commit(
+        &repo,
+        Path::new(drives::DRIVESFILE),
+        &format!("Add new drive: {}", name),
+    )?;
+    println!("Added new drive: {} ({})", name, kind);
+    Ok(())
+}
+
+pub(crate) fn cmd_drive_list(config_dir: &Path) -> Result<()> {
+    let drives = Drives::read(config_dir)?;
+    if drives.list.is_empty() {
+        println!("No drives found");
+        return Ok(());
+    }
+    let storages = Storages::read(config_dir).context("Failed to read storages")?;
+    let name_width = drives
+        .list
+        .keys()
+        .map(|name| name.width())
+        .max()
+        .unwrap_or(0);
+    let members = |drive: &Drive| {
+        if drive.members.is_empty() {
+            "".to_string()
+        } else {
+            format!("[{}]", drive.members.join(", "))
+        }
+    };
+    let members_width = drives
+        .list
+        .values()
+        .map(|drive| members(drive).width())
+        .max()
+        .unwrap_or(0);
+    for drive in drives.list.values() {
+        let partitions = storages
+            .list
+            .values()
+            .filter(|storage| {
+                matches!(storage, Storage::Physical(s) if s.drive.as_ref() == Some(drive.name()))
+            })
+            .map(|storage| storage.name().as_str())
+            .collect::<Vec<_>>();
+        let members = members(drive);
+        println!(
+            "{name} {kind:<4} {members:<members_width$} {partitions}",
+            name = Style::new()
+                .bold()
+                .apply_to(format!("{:<name_width$}", drive.name())),
+            kind = drive.kind,
+            partitions = partitions.join(", "),
+        );
+        if !drive.notes.is_empty() {
+            println!("  {}", Style::new().italic().apply_to(&drive.notes));
+        }
+    }
+    Ok(())
+}
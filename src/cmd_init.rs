@@ -4,11 +4,12 @@
 use crate::backups::Backups;
 use crate::storages::{STORAGESFILE, Storages};
 use crate::{
-    DEVICESFILE, Device, add_and_commit, backups,
-    devices::{get_devices, write_devices},
+    DEVICESFILE, Device, add_and_commit, add_and_commit_paths, backups,
+    devices::{DEVNAMEFILE, get_devices, write_devname, write_devices},
     full_status,
+    settings::SETTINGSFILE,
 };
-use anyhow::{Context, Ok, Result, anyhow};
+use anyhow::{Ok, Result, anyhow};
 use core::panic;
 use git2::{Cred, RemoteCallbacks, Repository};
 use inquire::Password;
@@ -16,6 +17,18 @@ use std::fs::{DirBuilder, File};
 use std::io::{BufWriter, Write};
 use std::path::{self, Path, PathBuf};
 
+/// Git attributes file of the config repository.
+pub(crate) const GITATTRIBUTES: &str = ".gitattributes";
+
+/// Write [`GITATTRIBUTES`] to merge log files by union
+/// since they are append-only.
+pub(crate) fn write_gitattributes(config_dir: &Path) -> Result<()> {
+    let f = File::create(config_dir.join(GITATTRIBUTES))?;
+    let mut buf = BufWriter::new(f);
+    writeln!(buf, "{}/** merge=union", backups::LOGSDIR)?;
+    Ok(())
+}
+
 fn clone_repo(
     repo_url: &str,
     use_sshagent: bool,
@@ -103,12 +116,21 @@ pub(crate) fn cmd_init(
                 let f = File::create(config_dir.join(".gitignore"))?;
                 {
                     let mut buf = BufWriter::new(f);
-                    buf.write_all("devname".as_bytes())?;
+                    writeln!(buf, "{}", DEVNAMEFILE)?;
+                    writeln!(buf, "{}", SETTINGSFILE)?;
                 }
                 add_and_commit(&repo, Path::new(".gitignore"), "Add devname to gitignore.")?;
                 full_status(&repo)?;
             }
 
+            // merge append-only logs without conflicts
+            write_gitattributes(config_dir)?;
+            add_and_commit(
+                &repo,
+                Path::new(GITATTRIBUTES),
+                "Add gitattributes for logs.",
+            )?;
+
             // TDOO: wrap up below into one commit?
             // set up devices.yml
             let devices: Vec<Device> = vec![];
@@ -141,13 +163,7 @@ pub(crate) fn cmd_init(
     trace!("Device information: {:?}", device);
 
     // save devname
-    let devname_path = &config_dir.join("devname");
-    {
-        let f = File::create(devname_path)
-            .context("Failed to create a file to store local device name")?;
-        let writer = BufWriter::new(f);
-        serde_yaml::to_writer(writer, &device.name()).unwrap();
-    };
+    write_devname(config_dir, &device.name())?;
     full_status(&repo)?;
 
     // Add new device to devices.yml
@@ -173,13 +189,13 @@ pub(crate) fn cmd_init(
     )?;
 
     // backups/[device].yml
-    {
+    let paths = {
         let backups = Backups::new();
-        backups.write(config_dir, &device)?;
-    }
-    add_and_commit(
+        backups.write(config_dir, &device)?
+    };
+    add_and_commit_paths(
         &repo,
-        &backups::backups_file(&device),
+        &paths,
         &format!("Add new backups for device: {}", &device.name()),
     )?;
 
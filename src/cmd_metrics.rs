@@ -0,0 +1,256 @@
+//! Metrics subcommand.
+//! Exports backups and storages status for the node_exporter textfile collector.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    backups::{Backup, Backups},
+    devices,
+    storages::{Storage, StorageExt, Storages},
+};
+
+pub(crate) fn cmd_metrics(prometheus: PathBuf, config_dir: &Path) -> Result<()> {
+    let devices = devices::get_devices(config_dir)?;
+    let storages = Storages::read(config_dir)?;
+    let backups = Backups::read_all(config_dir, &devices)?;
+    let content = prometheus_metrics(&backups, &storages);
+
+    // write to a temporary file and rename so that the collector never reads partial files
+    let mut tmp = prometheus.clone().into_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, content).context(format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, &prometheus).context(format!(
+        "Failed to rename {} to {}",
+        tmp.display(),
+        prometheus.display()
+    ))?;
+    println!("Metrics written to {}", prometheus.display());
+    Ok(())
+}
+
+/// Metrics in the Prometheus text exposition format.
+fn prometheus_metrics(backups: &[(String, Backup)], storages: &Storages) -> String {
+    let mut out = String::new();
+    let backup_labels = |device_name: &str, backup: &Backup| {
+        format!(
+            "backup=\"{}\",device=\"{}\",src=\"{}\",dest=\"{}\"",
+            escape_label(backup.name()),
+            escape_label(device_name),
+            escape_label(&backup.source().storage),
+            escape_label(&backup.destination().storage),
+        )
+    };
+
+    header(
+        &mut out,
+        "xdbm_backup_last_success_timestamp",
+        "Unix time of the last successful run of the backup.",
+    );
+    for (device_name, backup) in backups {
+        if let Some(log) = backup.last_success() {
+            let _ = writeln!(
+                out,
+                "xdbm_backup_last_success_timestamp{{{}}} {}",
+                backup_labels(device_name, backup),
+                log.datetime.timestamp()
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "xdbm_backup_last_run_timestamp",
+        "Unix time of the last run of the backup.",
+    );
+    for (device_name, backup) in backups {
+        if let Some(log) = backup.last_backup() {
+            let _ = writeln!(
+                out,
+                "xdbm_backup_last_run_timestamp{{{}}} {}",
+                backup_labels(device_name, backup),
+                log.datetime.timestamp()
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "xdbm_backup_last_status",
+        "1 if the last run of the backup completed, 0 otherwise.",
+    );
+    for (device_name, backup) in backups {
+        if let Some(log) = backup.last_backup() {
+            let _ = writeln!(
+                out,
+                "xdbm_backup_last_status{{{},status=\"{:?}\"}} {}",
+                backup_labels(device_name, backup),
+                log.status(),
+                u8::from(log.status().is_ok())
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "xdbm_backup_last_duration_seconds",
+        "Time taken by the last run of the backup.",
+    );
+    for (device_name, backup) in backups {
+        if let Some(duration) = backup.last_backup().and_then(|log| log.duration()) {
+            let _ = writeln!(
+                out,
+                "xdbm_backup_last_duration_seconds{{{}}} {}",
+                backup_labels(device_name, backup),
+                duration
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "xdbm_storage_capacity_bytes",
+        "Capacity of the storage.",
+    );
+    for storage in storages.list.values() {
+        if let Some(capacity) = storage.capacity() {
+            let _ = writeln!(
+                out,
+                "xdbm_storage_capacity_bytes{{{}}} {}",
+                storage_labels(storage),
+                capacity
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "xdbm_storage_free_bytes",
+        "Free bytes of the storage at the last refresh.",
+    );
+    for storage in storages.list.values() {
+        if let Some(record) = storage.usage().and_then(|usage| usage.latest()) {
+            let _ = writeln!(
+                out,
+                "xdbm_storage_free_bytes{{{}}} {}",
+                storage_labels(storage),
+                record.free
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "xdbm_storage_used_bytes",
+        "Used bytes of the storage at the last refresh.",
+    );
+    for storage in storages.list.values() {
+        if let Some(record) = storage.usage().and_then(|usage| usage.latest()) {
+            let _ = writeln!(
+                out,
+                "xdbm_storage_used_bytes{{{}}} {}",
+                storage_labels(storage),
+                record.used
+            );
+        }
+    }
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn storage_labels(storage: &Storage) -> String {
+    format!(
+        "storage=\"{}\",type=\"{}\"",
+        escape_label(storage.name()),
+        storage.typename()
+    )
+}
+
+/// Escape backslash, double quote and line feed in label values.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{
+        backups::{
+            Backup, BackupCommand, BackupLog, BackupResult, BackupTarget, ExternallyInvoked,
+        },
+        devices::Device,
+        storages::{Storage, Storages, online_storage::OnlineStorage},
+    };
+
+    use super::{escape_label, prometheus_metrics};
+
+    #[test]
+    fn escape_label_value() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn export_metrics() {
+        let device = Device::new("dev".to_string());
+        let mut storages = Storages::new();
+        storages
+            .add(Storage::Online(OnlineStorage::new(
+                "online".to_string(),
+                "provider".to_string(),
+                1_000_000,
+                "alias".to_string(),
+                PathBuf::from("/mnt/online"),
+                &device,
+            )))
+            .unwrap();
+        let mut backup = Backup::new(
+            "backup".to_string(),
+            device.name(),
+            BackupTarget::new("online".to_string(), PathBuf::from("src")).unwrap(),
+            BackupTarget::new("online".to_string(), PathBuf::from("dest")).unwrap(),
+            BackupCommand::ExternallyInvoked(ExternallyInvoked::new(
+                "manual".to_string(),
+                "".to_string(),
+            )),
+        );
+        let success =
+            BackupLog::new_with_current_time(BackupResult::Success, Some(0), "".to_string());
+        let success_time = success.datetime.timestamp();
+        backup.add_log(success);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        backup.add_log(BackupLog::new_with_current_time(
+            BackupResult::Failure,
+            Some(1),
+            "".to_string(),
+        ));
+
+        let metrics = prometheus_metrics(&[(device.name(), backup)], &storages);
+        let labels = "backup=\"backup\",device=\"dev\",src=\"online\",dest=\"online\"";
+        assert!(metrics.contains(&format!(
+            "xdbm_backup_last_success_timestamp{{{}}} {}\n",
+            labels, success_time
+        )));
+        assert!(metrics.contains(&format!(
+            "xdbm_backup_last_status{{{},status=\"Failure\"}} 0\n",
+            labels
+        )));
+        assert!(metrics.contains(
+            "xdbm_storage_capacity_bytes{storage=\"online\",type=\"OnlineStorage\"} 1000000\n"
+        ));
+        assert!(metrics.contains("# TYPE xdbm_storage_free_bytes gauge\n"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Migrate subcommand.
+//! Convert config files written by older versions to the current format.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use git2::Repository;
+
+use crate::{
+    add_and_commit_paths,
+    backups::{self, Backups},
+    cmd_init::{GITATTRIBUTES, write_gitattributes},
+    devices,
+    settings::SETTINGSFILE,
+};
+
+pub(crate) fn cmd_migrate(repo: Repository, config_dir: &Path) -> Result<()> {
+    let mut paths: Vec<PathBuf> = vec![];
+
+    if !config_dir.join(GITATTRIBUTES).exists() {
+        write_gitattributes(config_dir)?;
+        paths.push(PathBuf::from(GITATTRIBUTES));
+        println!("Added {}", GITATTRIBUTES);
+    }
+
+    // local settings must not be shared
+    let gitignore = config_dir.join(".gitignore");
+    let ignored = fs::read_to_string(&gitignore).unwrap_or_default();
+    if !ignored.lines().any(|line| line.trim() == SETTINGSFILE) {
+        let mut f = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&gitignore)?;
+        if !ignored.is_empty() && !ignored.ends_with('\n') {
+            writeln!(f)?;
+        }
+        writeln!(f, "{}", SETTINGSFILE)?;
+        paths.push(PathBuf::from(".gitignore"));
+        println!("Added {} to .gitignore", SETTINGSFILE);
+    }
+
+    // backup logs: backups/<device>.yml -> logs/<device>/<backup>.jsonl
+    for device in devices::get_devices(config_dir)? {
+        if !config_dir.join(backups::backups_file(&device)).exists() {
+            trace!("No backups file for device {}", device.name());
+            continue;
+        }
+        let backups = Backups::read(config_dir, &device)?;
+        let n_logs: usize = backups.list.values().map(|b| b.count_runs().0).sum();
+        paths.extend(backups.write(config_dir, &device)?);
+        println!("{}: {} logs", device.name(), n_logs);
+    }
+
+    let mut status_options = git2::StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true);
+    let statuses = repo.statuses(Some(&mut status_options))?;
+    let changed: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|path| {
+            statuses
+                .iter()
+                .any(|status| status.path().is_some_and(|p| Path::new(p) == path))
+        })
+        .collect();
+    if changed.is_empty() {
+        println!("Already up to date.");
+        return Ok(());
+    }
+    add_and_commit_paths(&repo, &changed, "Migrate config files")?;
+    println!("Migrated {} files.", changed.len());
+    Ok(())
+}
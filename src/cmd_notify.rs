@@ -0,0 +1,43 @@
+//! Notify subcommand.
+//! Check backups of the current device and notify failed or overdue ones.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    backups::Backups,
+    devices,
+    notify::{self, NOTIFYFILE, Notification, NotifyConfig},
+};
+
+pub(crate) fn cmd_notify(dry_run: bool, config_dir: &Path) -> Result<()> {
+    let config = NotifyConfig::read(config_dir)?.context(format!(
+        "Notifications are not configured in {}",
+        NOTIFYFILE
+    ))?;
+    let device = devices::get_device(config_dir)?;
+    let backups = Backups::read(config_dir, &device)?;
+    let notifications: Vec<Notification> = backups
+        .list
+        .values()
+        .filter_map(|backup| Notification::check(backup, &device.name(), config.overdue_days))
+        .collect();
+    if notifications.is_empty() {
+        println!("No failed or overdue backups.");
+        return Ok(());
+    }
+    let mut result = Ok(());
+    for notification in &notifications {
+        println!("{}", notification.render(&config.subject));
+        if dry_run {
+            continue;
+        }
+        if let Err(e) = notify::send(&config, notification)
+            && result.is_ok()
+        {
+            result = Err(e);
+        }
+    }
+    result
+}
@@ -0,0 +1,736 @@
+//! Report subcommand.
+//! Generates a self-contained static HTML site of storages and backups.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use byte_unit::{Byte, UnitType};
+use chrono::{DateTime, Local, TimeDelta};
+
+use crate::{
+    backups::{Backup, BackupLog, Backups},
+    devices::{self, Device},
+    drives::Drives,
+    storages::{StorageExt, Storages},
+    util,
+};
+
+/// Backups whose last run is older than this are reported as violations.
+const MAX_BACKUP_AGE_DAYS: i64 = 28;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+nav a { margin-right: 1em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+.age0 { background: #b7e4b0; }
+.age1 { background: #f3e9a0; }
+.age2 { background: #e8b0e0; }
+.age3 { background: #f0a0a0; }
+.age4 { background: #d85050; }
+.age5 { background: #902020; color: #fff; }
+.never { background: #eee; color: #888; }
+.success { color: #2a7a2a; }
+.failure { color: #b02020; }
+.muted { color: #888; }
+svg { background: #fafafa; border: 1px solid #ccc; }
+";
+
+pub(crate) fn cmd_report(html: PathBuf, config_dir: &Path) -> Result<()> {
+    let devices = devices::get_devices(config_dir)?;
+    let storages = Storages::read(config_dir)?;
+    let drives = Drives::read(config_dir)?;
+    let all_backups = Backups::read_all(config_dir, &devices)?;
+    let now = Local::now();
+
+    let write = |path: PathBuf, content: String| -> Result<()> {
+        let path = html.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .context(format!("Failed to create directory {}", parent.display()))?;
+        }
+        trace!("Writing {}", path.display());
+        fs::write(&path, content).context(format!("Failed to write {}", path.display()))
+    };
+    let violations = violations(&all_backups, &storages, &drives, now);
+    write(
+        PathBuf::from("index.html"),
+        index_page(&devices, &storages, &all_backups, &violations, now),
+    )?;
+    write(
+        PathBuf::from("storages.html"),
+        storages_page(&storages, &devices),
+    )?;
+    write(
+        PathBuf::from("matrix.html"),
+        matrix_page(&devices, &all_backups, now),
+    )?;
+    write(
+        PathBuf::from("violations.html"),
+        violations_page(&violations),
+    )?;
+    for (device_name, backup) in &all_backups {
+        write(
+            backup_page_path(device_name, backup.name()),
+            backup_page(device_name, backup),
+        )?;
+    }
+    println!("Report written to {}", html.display());
+    Ok(())
+}
+
+/// A backup which doesn't satisfy the backup policy.
+#[derive(Debug, PartialEq)]
+struct Violation {
+    device: String,
+    backup: String,
+    reason: String,
+}
+
+/// Find backups which were never run, are outdated, failed last time
+/// or point to unknown storages.
+fn violations(
+    backups: &[(String, Backup)],
+    storages: &Storages,
+    drives: &Drives,
+    now: DateTime<Local>,
+) -> Vec<Violation> {
+    let mut violations = vec![];
+    for (device_name, backup) in backups {
+        let mut push = |reason: String| {
+            violations.push(Violation {
+                device: device_name.clone(),
+                backup: backup.name().clone(),
+                reason,
+            })
+        };
+        for (role, target) in [
+            ("source", backup.source()),
+            ("destination", backup.destination()),
+        ] {
+            if !storages.list.contains_key(&target.storage) {
+                push(format!("Unknown {} storage {}", role, target.storage));
+            }
+        }
+        let same_source = backups
+            .iter()
+            .filter(|(_, other)| {
+                other.source().storage == backup.source().storage
+                    && other.source().path == backup.source().path
+            })
+            .collect::<Vec<_>>();
+        // copies are all on-site only if every destination has a recorded on-site location
+        let offsite = same_source
+            .iter()
+            .map(|(_, other)| {
+                storages
+                    .get(&other.destination().storage)
+                    .and_then(|storage| storages.location(storage))
+                    .map(|location| location.offsite)
+            })
+            .collect::<Vec<_>>();
+        if offsite.iter().all(|offsite| *offsite == Some(false)) {
+            push("No off-site copy".to_string());
+        }
+        // copies on the same disk fail together
+        let shared = drives.shared_disks(
+            storages,
+            &backup.source().storage,
+            &backup.destination().storage,
+        );
+        if !shared.is_empty() {
+            push(format!(
+                "Source and destination share disk {}",
+                shared.join(", ")
+            ));
+        }
+        for (other_device, other) in &same_source {
+            if (other_device, other.name()) == (device_name, backup.name()) {
+                continue;
+            }
+            let shared = drives.shared_disks(
+                storages,
+                &backup.destination().storage,
+                &other.destination().storage,
+            );
+            if !shared.is_empty() {
+                push(format!(
+                    "Not independent of backup {} (shares disk {})",
+                    other.name(),
+                    shared.join(", ")
+                ));
+            }
+        }
+        match backup.last_backup() {
+            None => push("Never run".to_string()),
+            Some(log) => {
+                let age = now - log.datetime;
+                if age > TimeDelta::days(MAX_BACKUP_AGE_DAYS) {
+                    push(format!(
+                        "Last run {} ago",
+                        util::format_summarized_duration(age)
+                    ));
+                }
+                if !log.status().is_ok() {
+                    push(format!("Last run was {:?}", log.status()));
+                }
+            }
+        }
+    }
+    violations
+}
+
+/// Path of the page of a backup relative to the report root.
+fn backup_page_path(device_name: &str, backup_name: &str) -> PathBuf {
+    PathBuf::from("backups")
+        .join(file_name(device_name))
+        .join(format!("{}.html", file_name(backup_name)))
+}
+
+fn backup_link(prefix: &str, device_name: &str, backup_name: &str) -> String {
+    // `%` in file names is escaped again in URLs
+    let href = format!(
+        "backups/{}/{}.html",
+        file_name(device_name),
+        file_name(backup_name)
+    )
+    .replace('%', "%25");
+    format!(
+        "<a href=\"{}{}\">{}</a>",
+        prefix,
+        escape(&href),
+        escape(backup_name)
+    )
+}
+
+/// Percent-encode `name` to use it as a file name in the report.
+/// Separators and leading dots are encoded so that the file stays in its directory.
+fn file_name(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for (i, b) in name.bytes().enumerate() {
+        match b {
+            b'.' if i > 0 => encoded.push('.'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => encoded.push(b as char),
+            b => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn page(title: &str, prefix: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title} - xdbm</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<nav><a href=\"{prefix}index.html\">Summary</a><a href=\"{prefix}storages.html\">Storages</a><a href=\"{prefix}matrix.html\">Backups</a><a href=\"{prefix}violations.html\">Violations</a></nav>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+",
+        title = escape(title),
+    )
+}
+
+fn index_page(
+    devices: &[Device],
+    storages: &Storages,
+    backups: &[(String, Backup)],
+    violations: &[Violation],
+    now: DateTime<Local>,
+) -> String {
+    let body = format!(
+        "<p>Generated at {}.</p>
+<ul>
+<li>{} devices</li>
+<li><a href=\"storages.html\">{} storages</a></li>
+<li><a href=\"matrix.html\">{} backups</a></li>
+<li><a href=\"violations.html\">{} violations</a></li>
+</ul>
+",
+        now.format("%Y-%m-%d %H:%M:%S"),
+        devices.len(),
+        storages.list.len(),
+        backups.len(),
+        violations.len(),
+    );
+    page("Summary", "", &body)
+}
+
+fn storages_page(storages: &Storages, devices: &[Device]) -> String {
+    let mut body = String::from(
+        "<table>\n<tr><th>Name</th><th>Type</th><th>Capacity</th><th>Usage</th><th>Mounted on</th></tr>\n",
+    );
+    for (depth, name) in storages.tree() {
+        let Some(storage) = storages.get(&name) else {
+            continue;
+        };
+        let capacity = storage.capacity().map_or("".to_string(), format_bytes);
+        let usage = match storage.usage().and_then(|usage| usage.latest()) {
+            Some(record) => format!(
+                "{} used, {} free ({})",
+                format_bytes(record.used),
+                format_bytes(record.free),
+                record.datetime.format("%Y-%m-%d")
+            ),
+            None => "".to_string(),
+        };
+        let mounts = devices
+            .iter()
+            .filter_map(|device| {
+                storage.local_info(device).map(|info| {
+                    format!(
+                        "{}: {}",
+                        escape(&device.name()),
+                        escape(&info.mount_path().to_string_lossy())
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("<br>");
+        let _ = writeln!(
+            body,
+            "<tr><td style=\"padding-left: {}em\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            0.6 + 1.5 * depth as f64,
+            escape(&name),
+            storage.typename(),
+            capacity,
+            usage,
+            mounts
+        );
+    }
+    body.push_str("</table>\n");
+    page("Storages", "", &body)
+}
+
+fn matrix_page(devices: &[Device], backups: &[(String, Backup)], now: DateTime<Local>) -> String {
+    let mut names = backups
+        .iter()
+        .map(|(_, backup)| backup.name().clone())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    let mut body = String::from("<table>\n<tr><th></th>");
+    for device in devices {
+        let _ = write!(body, "<th>{}</th>", escape(&device.name()));
+    }
+    body.push_str("</tr>\n");
+    for name in &names {
+        let _ = write!(body, "<tr><th>{}</th>", escape(name));
+        for device in devices {
+            let backup = backups
+                .iter()
+                .find(|(device_name, backup)| {
+                    *device_name == device.name() && backup.name() == name
+                })
+                .map(|(_, backup)| backup);
+            let cell = match backup {
+                None => "<td></td>".to_string(),
+                Some(backup) => match backup.last_backup() {
+                    None => format!(
+                        "<td class=\"never\">{}<br>never</td>",
+                        backup_link("", &device.name(), name)
+                    ),
+                    Some(log) => {
+                        let age = now - log.datetime;
+                        format!(
+                            "<td class=\"age{}\">{}<br>{} ago</td>",
+                            util::staleness_level(age),
+                            backup_link("", &device.name(), name),
+                            util::format_summarized_duration(age)
+                        )
+                    }
+                },
+            };
+            body.push_str(&cell);
+        }
+        body.push_str("</tr>\n");
+    }
+    body.push_str("</table>\n");
+    page("Backups", "", &body)
+}
+
+fn violations_page(violations: &[Violation]) -> String {
+    let body = if violations.is_empty() {
+        "<p>No violations.</p>\n".to_string()
+    } else {
+        let mut body =
+            String::from("<table>\n<tr><th>Device</th><th>Backup</th><th>Reason</th></tr>\n");
+        for violation in violations {
+            let _ = writeln!(
+                body,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&violation.device),
+                backup_link("", &violation.device, &violation.backup),
+                escape(&violation.reason)
+            );
+        }
+        body.push_str("</table>\n");
+        body
+    };
+    page("Violations", "", &body)
+}
+
+fn backup_page(device_name: &str, backup: &Backup) -> String {
+    let logs = backup.logs();
+    let mut body = format!(
+        "<p>Device: {}<br>Source: {} {}<br>Destination: {} {}</p>\n",
+        escape(device_name),
+        escape(&backup.source().storage),
+        escape(&backup.source().path.join("/")),
+        escape(&backup.destination().storage),
+        escape(&backup.destination().path.join("/")),
+    );
+    let durations = logs
+        .iter()
+        .filter_map(|log| log.duration().map(|d| (log.datetime, d)))
+        .collect::<Vec<_>>();
+    let bytes = logs
+        .iter()
+        .filter_map(|log| log.bytes().map(|b| (log.datetime, b)))
+        .collect::<Vec<_>>();
+    body.push_str(&chart("Duration", &durations, |d| format!("{}s", d)));
+    body.push_str(&chart("Size", &bytes, format_bytes));
+    body.push_str(
+        "<h2>Logs</h2>\n<table>\n<tr><th>Date</th><th>Status</th><th>Exit code</th><th>Duration</th><th>Size</th><th>Log</th></tr>\n",
+    );
+    for log in logs.iter().rev() {
+        body.push_str(&log_row(log));
+    }
+    body.push_str("</table>\n");
+    page(backup.name(), "../../", &body)
+}
+
+fn log_row(log: &BackupLog) -> String {
+    let class = if log.status().is_ok() {
+        "success"
+    } else {
+        "failure"
+    };
+    format!(
+        "<tr><td>{}</td><td class=\"{}\">{:?}</td><td>{}</td><td>{}</td><td>{}</td><td><pre>{}</pre></td></tr>\n",
+        log.datetime.format("%Y-%m-%d %H:%M:%S"),
+        class,
+        log.status(),
+        log.exit_code().map_or("".to_string(), |c| c.to_string()),
+        log.duration().map_or("".to_string(), |d| format!("{}s", d)),
+        log.bytes().map_or("".to_string(), format_bytes),
+        escape(log.log()),
+    )
+}
+
+/// Inline SVG line chart of `values` over time.
+fn chart(title: &str, values: &[(DateTime<Local>, u64)], label: impl Fn(u64) -> String) -> String {
+    const WIDTH: f64 = 600.0;
+    const HEIGHT: f64 = 160.0;
+    const MARGIN: f64 = 10.0;
+    let mut svg = format!("<h2>{}</h2>\n", escape(title));
+    if values.is_empty() {
+        svg.push_str("<p class=\"muted\">No data.</p>\n");
+        return svg;
+    }
+    let first = values[0].0.timestamp() as f64;
+    let span = (values[values.len() - 1].0.timestamp() as f64 - first).max(1.0);
+    let max = values.iter().map(|(_, v)| *v).max().unwrap_or(0).max(1) as f64;
+    let points = values
+        .iter()
+        .map(|(datetime, value)| {
+            let x = if values.len() == 1 {
+                WIDTH / 2.0
+            } else {
+                MARGIN + (datetime.timestamp() as f64 - first) / span * (WIDTH - 2.0 * MARGIN)
+            };
+            let y = HEIGHT - MARGIN - *value as f64 / max * (HEIGHT - 2.0 * MARGIN);
+            (x, y, *datetime, *value)
+        })
+        .collect::<Vec<_>>();
+    let _ = writeln!(
+        svg,
+        "<svg width=\"{WIDTH}\" height=\"{HEIGHT}\" viewBox=\"0 0 {WIDTH} {HEIGHT}\" xmlns=\"http://www.w3.org/2000/svg\">"
+    );
+    let _ = writeln!(
+        svg,
+        "<text x=\"{MARGIN}\" y=\"{MARGIN}\" font-size=\"10\" dominant-baseline=\"hanging\">max {}</text>",
+        escape(&label(max as u64))
+    );
+    let _ = writeln!(
+        svg,
+        "<polyline fill=\"none\" stroke=\"#3060c0\" stroke-width=\"2\" points=\"{}\"/>",
+        points
+            .iter()
+            .map(|(x, y, _, _)| format!("{:.1},{:.1}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    for (x, y, datetime, value) in points {
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"#3060c0\"><title>{}: {}</title></circle>",
+            x,
+            y,
+            datetime.format("%Y-%m-%d %H:%M"),
+            escape(&label(value))
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn format_bytes(bytes: u64) -> String {
+    format!(
+        "{:.2}",
+        Byte::from_u64(bytes).get_appropriate_unit(UnitType::Binary)
+    )
+}
+
+/// Escape special characters in HTML text and attribute values.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use chrono::{Local, TimeDelta};
+
+    use crate::{
+        backups::{
+            Backup, BackupCommand, BackupLog, BackupResult, BackupTarget, ExternallyInvoked,
+        },
+        devices::Device,
+        drives::{Drive, DriveKind, Drives},
+        storages::{
+            Storage, Storages,
+            local_info::LocalInfo,
+            online_storage::OnlineStorage,
+            physical_drive_partition::{DriveInfo, PhysicalDrivePartition},
+        },
+    };
+
+    use super::{Violation, backup_link, backup_page_path, escape, violations};
+
+    #[test]
+    fn escape_html() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn backup_page_names() {
+        assert_eq!(
+            backup_page_path("dev", "docs-1.2"),
+            PathBuf::from("backups/dev/docs-1.2.html")
+        );
+        assert_eq!(
+            backup_page_path("..", "../../x"),
+            PathBuf::from("backups/%2E./%2E.%2F..%2Fx.html")
+        );
+        assert_eq!(
+            backup_link("../", "dev", "my photos"),
+            "<a href=\"../backups/dev/my%2520photos.html\">my photos</a>"
+        );
+    }
+
+    #[test]
+    fn find_violations() {
+        let device = Device::new("dev".to_string());
+        let mut storages = Storages::new();
+        storages
+            .add(Storage::Online(OnlineStorage::new(
+                "online".to_string(),
+                "provider".to_string(),
+                1_000_000,
+                "alias".to_string(),
+                PathBuf::from("/mnt/online"),
+                &device,
+            )))
+            .unwrap();
+        let new_backup = |name: &str, dest: &str| {
+            Backup::new(
+                name.to_string(),
+                device.name(),
+                BackupTarget::new("online".to_string(), PathBuf::from("src")).unwrap(),
+                BackupTarget::new(dest.to_string(), PathBuf::from("dest")).unwrap(),
+                BackupCommand::ExternallyInvoked(ExternallyInvoked::new(
+                    "manual".to_string(),
+                    "".to_string(),
+                )),
+            )
+        };
+        let mut ok = new_backup("ok", "online");
+        ok.add_log(BackupLog::new_with_current_time(
+            BackupResult::Success,
+            Some(0),
+            "".to_string(),
+        ));
+        let never = new_backup("never", "missing");
+        let mut failed = new_backup("failed", "online");
+        failed.add_log(BackupLog::new_with_current_time(
+            BackupResult::Failure,
+            Some(1),
+            "".to_string(),
+        ));
+        let backups = vec![
+            (device.name(), ok.clone()),
+            (device.name(), never),
+            (device.name(), failed),
+        ];
+
+        let found = violations(&backups, &storages, &Drives::default(), Local::now());
+        let reasons = |name: &str| {
+            found
+                .iter()
+                .filter(|v| v.backup == name)
+                .map(|v| v.reason.clone())
+                .collect::<Vec<_>>()
+        };
+        assert!(reasons("ok").is_empty());
+        assert_eq!(
+            reasons("never"),
+            vec!["Unknown destination storage missing", "Never run"]
+        );
+        assert_eq!(reasons("failed"), vec!["Last run was Failure"]);
+
+        let later = violations(
+            &[(device.name(), ok)],
+            &storages,
+            &Drives::default(),
+            Local::now() + TimeDelta::days(40),
+        );
+        assert_eq!(
+            later,
+            vec![Violation {
+                device: "dev".to_string(),
+                backup: "ok".to_string(),
+                reason: "Last run 40d ago".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn copy_independence() {
+        let device = Device::new("dev".to_string());
+        let mut storages = Storages::new();
+        storages
+            .add(Storage::Online(OnlineStorage::new(
+                "online".to_string(),
+                "provider".to_string(),
+                1_000_000,
+                "alias".to_string(),
+                PathBuf::from("/mnt/online"),
+                &device,
+            )))
+            .unwrap();
+        let mut drives = Drives::default();
+        drives
+            .add(Drive::new(
+                "disk1".to_string(),
+                DriveKind::Disk,
+                vec![],
+                "".to_string(),
+            ))
+            .unwrap();
+        for name in ["home", "home2", "away"] {
+            let mut drive = PhysicalDrivePartition::new(
+                name.to_string(),
+                "SSD".to_string(),
+                1_000_000,
+                "ext4".to_string(),
+                true,
+                LocalInfo::new(name.to_string(), PathBuf::from("/mnt").join(name)),
+                &device,
+            );
+            drive.move_to(name.to_string(), name == "away");
+            if name != "away" {
+                drive.update_info(DriveInfo {
+                    drive: Some("disk1".to_string()),
+                    ..Default::default()
+                });
+            }
+            storages.add(Storage::Physical(drive)).unwrap();
+        }
+        let new_backup = |name: &str, src: (&str, &str), dest: &str| {
+            let mut backup = Backup::new(
+                name.to_string(),
+                device.name(),
+                BackupTarget::new(src.0.to_string(), PathBuf::from(src.1)).unwrap(),
+                BackupTarget::new(dest.to_string(), PathBuf::from("dest")).unwrap(),
+                BackupCommand::ExternallyInvoked(ExternallyInvoked::new(
+                    "manual".to_string(),
+                    "".to_string(),
+                )),
+            );
+            backup.add_log(BackupLog::new_with_current_time(
+                BackupResult::Success,
+                Some(0),
+                "".to_string(),
+            ));
+            backup
+        };
+        let backups = vec![
+            (device.name(), new_backup("onsite", ("online", "a"), "home")),
+            (device.name(), new_backup("b_home", ("online", "b"), "home")),
+            (device.name(), new_backup("b_away", ("online", "b"), "away")),
+            (device.name(), new_backup("c_home", ("online", "c"), "home")),
+            (
+                device.name(),
+                new_backup("c_home2", ("online", "c"), "home2"),
+            ),
+            (device.name(), new_backup("c_away", ("online", "c"), "away")),
+            (
+                device.name(),
+                new_backup("same_disk", ("home", "d"), "home2"),
+            ),
+        ];
+        let found = violations(&backups, &storages, &drives, Local::now());
+        let reasons = |name: &str| {
+            found
+                .iter()
+                .filter(|v| v.backup == name)
+                .map(|v| v.reason.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(reasons("onsite"), vec!["No off-site copy"]);
+        assert!(reasons("b_home").is_empty());
+        assert!(reasons("b_away").is_empty());
+        assert_eq!(
+            reasons("c_home"),
+            vec!["Not independent of backup c_home2 (shares disk disk1)"]
+        );
+        assert_eq!(
+            reasons("c_home2"),
+            vec!["Not independent of backup c_home (shares disk disk1)"]
+        );
+        assert!(reasons("c_away").is_empty());
+        assert_eq!(
+            reasons("same_disk"),
+            vec![
+                "No off-site copy",
+                "Source and destination share disk disk1"
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Schedule subcommand.
+//! Export backup schedules to systemd timers or crontab.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    backups::{Backup, BackupCommand, Backups, schedule::Schedule},
+    devices,
+};
+
+/// File name of the crontab written to the output directory.
+const CRONTAB_FILE: &str = "xdbm.crontab";
+
+pub(crate) fn cmd_schedule_export(
+    systemd: bool,
+    output: Option<PathBuf>,
+    config_dir: &Path,
+) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let backups = Backups::read(config_dir, &device)?;
+    let exe = env::current_exe().context("Failed to get the path of xdbm")?;
+    let config_dir = dunce::canonicalize(config_dir)?;
+    let scheduled: Vec<(&Backup, &Schedule)> = backups
+        .list
+        .values()
+        .filter_map(|backup| backup.schedule().map(|schedule| (backup, schedule)))
+        .filter(|(backup, _)| {
+            let runnable = !matches!(backup.command(), BackupCommand::ExternallyInvoked(_));
+            if !runnable {
+                eprintln!(
+                    "{} {} is externally invoked and can't be run by xdbm. Skipped.",
+                    console::style("Warning:").yellow().bold(),
+                    backup.name()
+                );
+            }
+            runnable
+        })
+        .collect();
+    if scheduled.is_empty() {
+        eprintln!("No scheduled backups on {}.", device.name());
+    }
+
+    let files: Vec<(String, String)> = if systemd {
+        scheduled
+            .iter()
+            .flat_map(|(backup, schedule)| {
+                let unit = unit_name(backup.name());
+                [
+                    (
+                        format!("{}.service", unit),
+                        systemd_service(backup.name(), &exe, &config_dir),
+                    ),
+                    (
+                        format!("{}.timer", unit),
+                        systemd_timer(backup.name(), schedule),
+                    ),
+                ]
+            })
+            .collect()
+    } else {
+        let mut crontab = format!("# xdbm backups on {}\n", device.name());
+        for (backup, schedule) in &scheduled {
+            crontab.push_str(&cron_entry(backup.name(), schedule, &exe, &config_dir));
+        }
+        vec![(CRONTAB_FILE.to_string(), crontab)]
+    };
+
+    match output {
+        Some(output) => {
+            fs::create_dir_all(&output)?;
+            for (name, content) in files {
+                let path = output.join(name);
+                fs::write(&path, content).context(format!("Failed to write {}", path.display()))?;
+                println!("Wrote {}", path.display());
+            }
+        }
+        None if systemd => {
+            for (name, content) in files {
+                println!("# {}\n{}", name, content);
+            }
+        }
+        None => {
+            for (_, content) in files {
+                print!("{}", content);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// systemd unit name for the backup, with characters not allowed replaced by `_`.
+fn unit_name(backup_name: &str) -> String {
+    let escaped: String = backup_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("xdbm-backup-{}", escaped)
+}
+
+fn systemd_service(backup_name: &str, exe: &Path, config_dir: &Path) -> String {
+    let quote = |s: &str| {
+        format!(
+            "\"{}\"",
+            s.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('%', "%%")
+                .replace('$', "$$")
+        )
+    };
+    format!(
+        "[Unit]
+Description=xdbm backup {name}
+
+[Service]
+Type=oneshot
+ExecStart={exe} -c {config_dir} backup run {quoted_name}
+",
+        name = backup_name.replace('%', "%%"),
+        exe = quote(&exe.to_string_lossy()),
+        config_dir = quote(&config_dir.to_string_lossy()),
+        quoted_name = quote(backup_name),
+    )
+}
+
+fn systemd_timer(backup_name: &str, schedule: &Schedule) -> String {
+    format!(
+        "[Unit]
+Description=Run xdbm backup {name} {schedule}
+
+[Timer]
+OnCalendar={on_calendar}
+Persistent=true
+
+[Install]
+WantedBy=timers.target
+",
+        name = backup_name.replace('%', "%%"),
+        on_calendar = schedule.on_calendar(),
+    )
+}
+
+fn cron_entry(backup_name: &str, schedule: &Schedule, exe: &Path, config_dir: &Path) -> String {
+    // `%` is a newline in crontab
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "'\\''").replace('%', "\\%"));
+    format!(
+        "{} {} -c {} backup run {}\n",
+        schedule.cron(),
+        quote(&exe.to_string_lossy()),
+        quote(&config_dir.to_string_lossy()),
+        quote(backup_name)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::backups::schedule::Schedule;
+
+    use super::{cron_entry, systemd_service, systemd_timer, unit_name};
+
+    #[test]
+    fn systemd_units() {
+        assert_eq!(unit_name("my photos"), "xdbm-backup-my_photos");
+        let service = systemd_service("100%", Path::new("/usr/bin/xdbm"), Path::new("/home/a"));
+        assert!(
+            service.contains("ExecStart=\"/usr/bin/xdbm\" -c \"/home/a\" backup run \"100%%\"\n")
+        );
+        let timer = systemd_timer("photos", &Schedule::Hours(6));
+        assert!(timer.contains("OnCalendar=*-*-* 00/6:00:00\n"));
+    }
+
+    #[test]
+    fn crontab_entry() {
+        assert_eq!(
+            cron_entry(
+                "it's",
+                &Schedule::Daily,
+                Path::new("/usr/bin/xdbm"),
+                Path::new("/home/a")
+            ),
+            "0 0 * * * '/usr/bin/xdbm' -c '/home/a' backup run 'it'\\''s'\n"
+        );
+    }
+}
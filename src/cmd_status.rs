@@ -18,6 +18,7 @@ pub(crate) fn cmd_status(
     path: Option<PathBuf>,
     show_storage: bool,
     show_backup: bool,
+    show_copies: bool,
     config_dir: &Path,
 ) -> Result<()> {
     let path = path.unwrap_or(env::current_dir().context("Failed to get current directory.")?);
@@ -38,7 +39,7 @@ pub(crate) fn cmd_status(
             }
         }
     }
-    if show_backup {
+    if show_backup || show_copies {
         let devices = devices::get_devices(config_dir)?;
         let storages = storages::Storages::read(config_dir)?;
         let backups = devices.iter().map(|device| {
@@ -94,6 +95,22 @@ pub(crate) fn cmd_status(
             .max()
             .unwrap_or(5);
 
+        if show_copies {
+            println!("Copies:");
+            for (backup_device, covering_backups) in &covering_backup {
+                for (backup, path_from_backup) in covering_backups {
+                    println!(
+                        "  {:<name_len$} {}",
+                        console::style(backup.name()).bold(),
+                        copy_location(backup, path_from_backup, &storages, backup_device),
+                    );
+                }
+            }
+            if !show_backup {
+                return Ok(());
+            }
+        }
+
         for (backup_device, covering_backups) in covering_backup {
             if covering_backups.is_empty() {
                 continue;
@@ -111,10 +128,15 @@ pub(crate) fn cmd_status(
                     }
                     None => ("---".to_string(), Style::new().red()),
                 };
+                let last_status = match backup.last_backup() {
+                    Some(log) => log.status().style().apply_to(log.status().to_string()),
+                    None => Style::new().apply_to("".to_string()),
+                };
                 println!(
-                    "  {:<name_len$} {} {}",
+                    "  {:<name_len$} {} {} {}",
                     console::style(backup.name()).bold(),
                     style.apply_to(last_backup),
+                    last_status,
                     path_from_backup.display(),
                 );
             }
@@ -124,6 +146,43 @@ pub(crate) fn cmd_status(
     Ok(())
 }
 
+/// Where the copy of the path `path_from_backup` in the source of `backup` is,
+/// with a note if it is not browsable as plain files.
+fn copy_location(
+    backup: &Backup,
+    path_from_backup: &Path,
+    storages: &Storages,
+    device: &Device,
+) -> String {
+    let mut target = backup.destination().clone();
+    target.path.extend(
+        path_from_backup
+            .components()
+            .filter_map(|c| c.as_os_str().to_str().map(|s| s.to_owned()))
+            .filter(|s| !s.is_empty()),
+    );
+    let location = target
+        .location(storages, device)
+        .unwrap_or_else(|| format!("(not available on {})", device.name()));
+    let note = match storages.get(&target.storage) {
+        Some(Storage::Archive(archive)) => {
+            format!(" [in {}, not browsable]", archive.description())
+        }
+        Some(Storage::Offline(media)) => format!(" [{}]", media.description()),
+        _ => "".to_string(),
+    };
+    let site = match storages
+        .get(&target.storage)
+        // offline media shows its location in the description
+        .filter(|storage| !matches!(storage, Storage::Offline(_)))
+        .and_then(|storage| storages.location(storage))
+    {
+        Some(location) => format!(" [{}: {}]", location.site(), location.location),
+        None => "".to_string(),
+    };
+    format!("{}: {}{}{}", target.storage, location, note, site)
+}
+
 /// Get [`Backup`]s for `device` which covers `target_path`.
 /// Returns [`Vec`] of tuple of [`Backup`] and relative path from the backup root.
 fn parent_backups<'a>(
@@ -7,19 +7,23 @@ use std::{
 
 use anyhow::{anyhow, Context, Result};
 use byte_unit::{Byte, UnitType};
+use chrono::Local;
 use console::style;
 use dunce::canonicalize;
 use git2::Repository;
 use inquire::{Confirm, CustomType, Text};
+use sysinfo::Disks;
 use unicode_width::{self, UnicodeWidthStr};
 
 use crate::{
     add_and_commit,
-    cmd_args::StorageAddCommands,
+    cmd_args::{PhysicalDriveArgs, StorageAddCommands},
     devices::{self, Device},
+    drives::Drives,
     storages::{
         self, directory, local_info,
-        physical_drive_partition::{self, PhysicalDrivePartition},
+        physical_drive_partition::{self, DriveInfo, PhysicalDrivePartition},
+        usage::{self, UsageRecord},
         Storage, StorageExt, Storages,
     },
     util,
@@ -37,22 +41,34 @@ pub(crate) fn cmd_storage_add(
 
     let device = devices::get_device(config_dir)?;
     let storage = match args {
-        StorageAddCommands::Physical { name, path } => {
+        StorageAddCommands::Physical {
+            name,
+            path,
+            from_mount,
+            fields,
+        } => {
             if !is_unique_name(&name, &storages) {
                 return Err(anyhow!(
                     "The name {} is already used for another storage.",
                     name
                 ));
             }
-            let use_sysinfo = path.is_none();
-            let storage = if use_sysinfo {
-                physical_drive_partition::select_physical_storage(name, device)?
-            } else {
-                manually_construct_physical_drive_partition(
+            let storage = match (path, from_mount) {
+                (_, Some(mount_point)) => physical_drive_partition::find_physical_storage(
+                    Disks::new_with_refreshed_list()
+                        .iter()
+                        .map(physical_drive_partition::MountedDisk::from),
+                    &canonicalize(util::expand_tilde(mount_point)?)?,
+                    name,
+                    device,
+                )?,
+                (Some(path), None) => manually_construct_physical_drive_partition(
                     name,
-                    canonicalize(util::expand_tilde(path.unwrap())?)?,
+                    canonicalize(util::expand_tilde(path)?)?,
+                    fields,
                     &device,
-                )?
+                )?,
+                (None, None) => physical_drive_partition::select_physical_storage(name, device)?,
             };
             println!("storage: {}: {:?}", storage.name(), storage);
             Storage::Physical(storage)
@@ -105,6 +121,100 @@ pub(crate) fn cmd_storage_add(
             );
             Storage::Online(storage)
         }
+        StorageAddCommands::Network {
+            name,
+            path,
+            server,
+            share,
+            protocol,
+            capacity,
+            alias,
+        } => {
+            if !is_unique_name(&name, &storages) {
+                return Err(anyhow!(
+                    "The name {} is already used for another storage.",
+                    name
+                ));
+            }
+            // the share may not be mounted now
+            let path = std::path::absolute(util::expand_tilde(path)?)?;
+            let storage = storages::network_share::NetworkShare::new(
+                name, server, share, protocol, capacity, alias, path, &device,
+            );
+            if storage.is_mounted(&device) == Some(false) {
+                println!(
+                    "{} The share is not mounted on {} now.",
+                    style("Note:").yellow(),
+                    device.name()
+                );
+            }
+            Storage::Network(storage)
+        }
+        StorageAddCommands::Archive {
+            name,
+            path,
+            format,
+            encrypted,
+            repository_id,
+            notes,
+        } => {
+            if !is_unique_name(&name, &storages) {
+                return Err(anyhow!(
+                    "The name {} is already used for another storage.",
+                    name
+                ));
+            }
+            let path = canonicalize(util::expand_tilde(path)?)?;
+            let storage = storages::archive::Archive::try_from_device_path(
+                name,
+                path,
+                format,
+                encrypted,
+                repository_id,
+                notes,
+                &device,
+                &storages,
+            )?;
+            Storage::Archive(storage)
+        }
+        StorageAddCommands::Offline {
+            name,
+            kind,
+            label,
+            location,
+            written,
+            capacity,
+        } => {
+            if !is_unique_name(&name, &storages) {
+                return Err(anyhow!(
+                    "The name {} is already used for another storage.",
+                    name
+                ));
+            }
+            let written = written.unwrap_or_else(|| Local::now().date_naive());
+            Storage::Offline(storages::offline_media::OfflineMedia::new(
+                name, kind, label, location, written, capacity,
+            ))
+        }
+        StorageAddCommands::Rclone { name, remote } => {
+            if !is_unique_name(&name, &storages) {
+                return Err(anyhow!(
+                    "The name {} is already used for another storage.",
+                    name
+                ));
+            }
+            let mut storage = storages::rclone_remote::RcloneRemote::new(name, remote);
+            match storage.about() {
+                Ok(record) => storage.usage_mut().unwrap().push(record),
+                Err(e) => eprintln!(
+                    "{} Couldn't get the usage of {}: {:#}",
+                    style("Warning:").yellow().bold(),
+                    storage.remote,
+                    e
+                ),
+            }
+            Storage::Rclone(storage)
+        }
     };
 
     // add to storages
@@ -135,27 +245,44 @@ fn is_unique_name(newname: &String, storages: &Storages) -> bool {
     storages.list.iter().all(|(name, _)| name != newname)
 }
 
+/// Construct [`PhysicalDrivePartition`] from `fields`, asking the missing ones.
 fn manually_construct_physical_drive_partition(
     name: String,
     path: PathBuf,
+    fields: PhysicalDriveArgs,
     device: &Device,
 ) -> Result<PhysicalDrivePartition> {
-    let kind = Text::new("Kind of storage (ex. SSD):")
-        .prompt()
-        .context("Failed to get kind.")?;
-    let capacity: u64 = CustomType::<u64>::new("Capacity (byte):")
-        .with_error_message("Please type number.")
-        .prompt()
-        .context("Failed to get capacity.")?;
-    let fs = Text::new("filesystem:")
-        .prompt()
-        .context("Failed to get fs.")?;
-    let is_removable = Confirm::new("Is removable")
-        .prompt()
-        .context("Failed to get is_removable")?;
-    let alias = Text::new("Alias of the storage for this device")
-        .prompt()
-        .context("Failed to get alias.")?;
+    let kind = match fields.kind {
+        Some(kind) => kind,
+        None => Text::new("Kind of storage (ex. SSD):")
+            .prompt()
+            .context("Failed to get kind.")?,
+    };
+    let capacity: u64 = match fields.capacity {
+        Some(capacity) => capacity,
+        None => CustomType::<u64>::new("Capacity (byte):")
+            .with_error_message("Please type number.")
+            .prompt()
+            .context("Failed to get capacity.")?,
+    };
+    let fs = match fields.fs {
+        Some(fs) => fs,
+        None => Text::new("filesystem:")
+            .prompt()
+            .context("Failed to get fs.")?,
+    };
+    let is_removable = match fields.removable {
+        Some(is_removable) => is_removable,
+        None => Confirm::new("Is removable")
+            .prompt()
+            .context("Failed to get is_removable")?,
+    };
+    let alias = match fields.alias {
+        Some(alias) => alias,
+        None => Text::new("Alias of the storage for this device")
+            .prompt()
+            .context("Failed to get alias.")?,
+    };
     let local_info = local_info::LocalInfo::new(alias, path);
     Ok(physical_drive_partition::PhysicalDrivePartition::new(
         name,
@@ -214,28 +341,51 @@ fn write_storages_list(
         } else {
             ""
         };
-        let path = storage.mount_path(device).map_or_else(
-            || {
-                info!("Mount path not found");
-                "".to_string()
-            },
-            |v| v.display().to_string(),
-        );
-        let parent_name = if let Storage::SubDirectory(s) = storage {
-            s.parent(storages)
-                .context(format!("Failed to get parent of storage {}", s))?
-                .name()
-        } else {
-            ""
+        let mount_path = match storage {
+            Storage::Archive(s) => s.path(storages, device),
+            _ => storage.mount_path(device),
+        };
+        let path = match storage {
+            Storage::Offline(s) => s.label.clone(),
+            _ => mount_path.map_or_else(
+                || {
+                    info!("Mount path not found");
+                    "".to_string()
+                },
+                |v| v.display().to_string(),
+            ),
+        };
+        let parent_name = match storage {
+            Storage::SubDirectory(_) | Storage::Archive(_) => storage
+                .parent(storages)
+                .context(format!("Failed to get parent of storage {}", storage))?
+                .name(),
+            _ => "",
+        };
+        let latest_usage = storage.usage().and_then(|usage| usage.latest());
+        let usage_str = match latest_usage.and_then(|record| record.used_percent()) {
+            Some(percent) => format!("{:>3.0}%", percent),
+            None => "".to_string(),
+        };
+        let path = match storage.is_mounted(device) {
+            Some(false) => format!("{} {}", path, style("(not mounted)").red()),
+            _ => path,
+        };
+        let path = match storage {
+            Storage::Physical(s) if !s.status.is_active() => {
+                format!("{} {}", path, style(format!("({})", s.status)).red())
+            }
+            _ => path,
         };
         let typestyle = storage.typestyle();
         writeln!(
             writer,
-            "{stype}{isremovable:<1}: {name:<name_width$} {size:>10} {parent:<name_width$} {path}",
+            "{stype}{isremovable:<1}: {name:<name_width$} {size:>10} {usage:>4} {parent:<name_width$} {path}",
             stype = typestyle.apply_to(storage.shorttypename()),
             isremovable = isremovable,
             name = typestyle.apply_to(storage.name()),
             size = size_str,
+            usage = usage_style(latest_usage).apply_to(usage_str),
             parent = console::style(parent_name).bright().black(),
             path = path,
         )?;
@@ -244,13 +394,125 @@ fn write_storages_list(
                 Storage::Physical(s) => format!("kind: {}", s.kind()),
                 Storage::SubDirectory(s) => s.notes.clone(),
                 Storage::Online(s) => s.provider.clone(),
+                Storage::Network(s) => s.url(),
+                Storage::Rclone(s) => s.remote.clone(),
+                Storage::Archive(s) if s.notes.is_empty() => s.description(),
+                Storage::Archive(s) => format!("{}: {}", s.description(), s.notes),
+                Storage::Offline(s) => s.description(),
             };
             writeln!(writer, "  {}", style(note).italic())?;
+            if let Storage::Physical(s) = storage
+                && let Some(description) = s.lifecycle_description()
+            {
+                writeln!(writer, "  {}", style(description).italic())?;
+            }
+            if let Storage::Physical(s) = storage
+                && let Some(location) = s.location()
+            {
+                writeln!(
+                    writer,
+                    "  {}",
+                    style(format!(
+                        "location: {} ({}, since {})",
+                        location.location,
+                        location.site(),
+                        location.datetime.format("%Y-%m-%d"),
+                    ))
+                    .italic()
+                )?;
+            }
+            if let Some(usage) = storage.usage()
+                && let Some(latest) = usage.latest()
+            {
+                let prediction = match usage.predict_full() {
+                    Some(date) => format!(", full at {}", date.format("%Y-%m-%d")),
+                    None => "".to_string(),
+                };
+                writeln!(
+                    writer,
+                    "  {}",
+                    style(format!(
+                        "used: {:.1}, free: {:.1} ({}){}",
+                        Byte::from_u64(latest.used).get_appropriate_unit(UnitType::Binary),
+                        Byte::from_u64(latest.free).get_appropriate_unit(UnitType::Binary),
+                        latest.datetime.format("%Y-%m-%d %T"),
+                        prediction,
+                    ))
+                    .italic()
+                )?;
+            }
         }
     }
     Ok(())
 }
 
+/// Style for used ratio of the storage.
+pub(crate) fn usage_style(record: Option<&UsageRecord>) -> console::Style {
+    match record.and_then(|record| record.used_percent()) {
+        Some(x) if x < 80.0 => console::Style::new().green(),
+        Some(x) if x < 90.0 => console::Style::new().yellow(),
+        Some(_) => console::Style::new().red(),
+        None => console::Style::new(),
+    }
+}
+
+pub(crate) fn cmd_storage_refresh(repo: Repository, config_dir: &Path) -> Result<()> {
+    let device = devices::get_device(config_dir)?;
+    let mut storages = Storages::read(config_dir)?;
+    let disks = Disks::new_with_refreshed_list();
+    trace!("Available disks: {:?}", disks);
+    let mut n_refreshed = 0;
+    for storage in storages.list.values_mut() {
+        let record = if let Storage::Rclone(remote) = storage {
+            // rclone remotes are queried regardless of the mount
+            match remote.about() {
+                Ok(record) => record,
+                Err(e) => {
+                    eprintln!("{}: {:#}", remote.name(), e);
+                    continue;
+                }
+            }
+        } else {
+            let Some(mount_path) = storage.mount_path(&device) else {
+                continue;
+            };
+            // physical drive and network share are available only when mounted on the exact path
+            let exact = matches!(storage, Storage::Physical(_) | Storage::Network(_));
+            let Some(disk) = usage::find_disk(&disks, &mount_path, exact) else {
+                debug!("Storage {} is not mounted", storage.name());
+                continue;
+            };
+            usage::measure(disk)
+        };
+        let name = storage.name().clone();
+        let Some(usage) = storage.usage_mut() else {
+            continue;
+        };
+        println!(
+            "{name}: used {used:.1}, free {free:.1}",
+            used = Byte::from_u64(record.used).get_appropriate_unit(UnitType::Binary),
+            free = Byte::from_u64(record.free).get_appropriate_unit(UnitType::Binary),
+        );
+        usage.push(record);
+        n_refreshed += 1;
+    }
+    if n_refreshed == 0 {
+        println!("No storages are mounted on this device");
+        return Ok(());
+    }
+    storages.write(config_dir)?;
+    add_and_commit(
+        &repo,
+        Path::new(storages::STORAGESFILE),
+        &format!(
+            "Refresh usage of {} storages on {}",
+            n_refreshed,
+            device.name()
+        ),
+    )?;
+    Ok(())
+}
+
 pub(crate) fn cmd_storage_bind(
     storage_name: String,
     new_alias: String,
@@ -298,6 +560,100 @@ pub(crate) fn cmd_storage_bind(
     Ok(())
 }
 
+pub(crate) fn cmd_storage_move(
+    storage_name: String,
+    location: String,
+    offsite: bool,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    let mut storages = Storages::read(config_dir)?;
+    let storage = storages
+        .list
+        .get_mut(&storage_name)
+        .context(format!("No storage has name {}", storage_name))?;
+    let (previous, current) = match storage {
+        Storage::Physical(drive) => {
+            let previous = drive.location().cloned();
+            drive.move_to(location.clone(), offsite);
+            (previous, drive.location().cloned())
+        }
+        Storage::Offline(media) => {
+            let previous = media.location().cloned();
+            media.move_to(location.clone(), offsite);
+            (previous, media.location().cloned())
+        }
+        _ => {
+            return Err(anyhow!(
+                "Storage {} is neither a physical drive nor offline media, which can be moved.",
+                storage_name
+            ));
+        }
+    };
+    if let Some(previous) = previous {
+        println!(
+            "{} was at {} ({}) since {}",
+            storage_name,
+            previous.location,
+            previous.site(),
+            previous.datetime.format("%Y-%m-%d"),
+        );
+    }
+    let site = current.context("Location is not recorded")?.site();
+    storages.write(config_dir)?;
+    add_and_commit(
+        &repo,
+        Path::new(storages::STORAGESFILE),
+        &format!("Move storage {} to {} ({})", storage_name, location, site),
+    )?;
+    println!("Moved {} to {} ({})", storage_name, location, site);
+    Ok(())
+}
+
+pub(crate) fn cmd_storage_edit(
+    storage_name: String,
+    info: DriveInfo,
+    repo: Repository,
+    config_dir: &Path,
+) -> Result<()> {
+    if let Some(drive) = &info.drive
+        && Drives::read(config_dir)?.get(drive).is_none()
+    {
+        return Err(anyhow!(
+            "No drive has name {}. Add it by `drive add` first.",
+            drive
+        ));
+    }
+    let mut storages = Storages::read(config_dir)?;
+    let storage = storages
+        .list
+        .get_mut(&storage_name)
+        .context(format!("No storage has name {}", storage_name))?;
+    let Storage::Physical(drive) = storage else {
+        return Err(anyhow!(
+            "Storage {} is not a physical drive. Only physical drives have lifecycle info.",
+            storage_name
+        ));
+    };
+    let message = match info.status {
+        Some(status) if status != drive.status => {
+            format!("Mark storage {} {}", storage_name, status)
+        }
+        _ => format!("Update info of storage {}", storage_name),
+    };
+    drive.update_info(info);
+    println!(
+        "{}: {}",
+        storage_name,
+        drive
+            .lifecycle_description()
+            .unwrap_or_else(|| format!("status: {}", drive.status))
+    );
+    storages.write(config_dir)?;
+    add_and_commit(&repo, Path::new(storages::STORAGESFILE), &message)?;
+    Ok(())
+}
+
 fn ask_unique_name(storages: &Storages, target: String) -> Result<String> {
     let mut disk_name = String::new();
     loop {
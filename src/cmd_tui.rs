@@ -0,0 +1,500 @@
+//! Tui subcommand.
+//! Full-screen dashboard of storages, backups and their logs.
+
+use std::{
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use git2::Repository;
+use inquire::{CustomType, Text};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+};
+
+use crate::{
+    backups::{self, Backup, Backups},
+    cmd_backup, cmd_check, cmd_storage, cmd_sync,
+    devices::{self, Device},
+    inquire_filepath_completer::FilePathCompleter,
+    settings::Settings,
+    storages::{Storage, StorageExt, Storages},
+    util,
+};
+
+const HELP: &str =
+    "q: quit  tab: switch pane  j/k: move  b: bind  d: backup done  s: sync  c: check  r: reload";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Storages,
+    Backups,
+    Logs,
+}
+
+impl Pane {
+    fn next(self) -> Pane {
+        match self {
+            Pane::Storages => Pane::Backups,
+            Pane::Backups => Pane::Logs,
+            Pane::Logs => Pane::Storages,
+        }
+    }
+
+    fn prev(self) -> Pane {
+        match self {
+            Pane::Storages => Pane::Logs,
+            Pane::Backups => Pane::Storages,
+            Pane::Logs => Pane::Backups,
+        }
+    }
+}
+
+/// Actions which leave the full-screen UI to run the CLI commands.
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Bind,
+    Done,
+    Sync,
+    Check,
+}
+
+struct App {
+    config_dir: PathBuf,
+    settings: Settings,
+    device: Device,
+    storages: Storages,
+    /// Storage names in the tree order with their depth.
+    storage_rows: Vec<(usize, String)>,
+    /// Backups of all devices with the device name.
+    backups: Vec<(String, Backup)>,
+    focus: Pane,
+    storage_state: ListState,
+    backup_state: ListState,
+    log_state: ListState,
+    message: String,
+}
+
+pub(crate) fn cmd_tui(config_dir: &Path, settings: Settings) -> Result<()> {
+    let mut app = App {
+        config_dir: config_dir.to_path_buf(),
+        settings,
+        device: devices::get_device(config_dir)?,
+        storages: Storages::new(),
+        storage_rows: vec![],
+        backups: vec![],
+        focus: Pane::Backups,
+        storage_state: ListState::default(),
+        backup_state: ListState::default(),
+        log_state: ListState::default(),
+        message: String::new(),
+    };
+    app.reload()?;
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let action = match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab => {
+                    self.focus = self.focus.next();
+                    None
+                }
+                KeyCode::BackTab => {
+                    self.focus = self.focus.prev();
+                    None
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.move_selection(1);
+                    None
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.move_selection(-1);
+                    None
+                }
+                KeyCode::Char('r') => {
+                    self.message = match self.reload() {
+                        Ok(()) => "Reloaded".to_string(),
+                        Err(e) => format!("Failed to reload: {e}"),
+                    };
+                    None
+                }
+                KeyCode::Char('b') => Some(Action::Bind),
+                KeyCode::Char('d') => Some(Action::Done),
+                KeyCode::Char('s') => Some(Action::Sync),
+                KeyCode::Char('c') => Some(Action::Check),
+                _ => None,
+            };
+            if let Some(action) = action {
+                ratatui::restore();
+                let result = self.perform(action);
+                self.message = match &result {
+                    Ok(()) => format!("{:?} finished", action),
+                    Err(e) => format!("{:?} failed: {e}", action),
+                };
+                if let Err(e) = result {
+                    eprintln!("Error: {e:?}");
+                }
+                wait_enter()?;
+                *terminal = ratatui::init();
+                if let Err(e) = self.reload() {
+                    self.message = format!("Failed to reload: {e}");
+                }
+            }
+        }
+    }
+
+    /// Read all data again from the config dir.
+    fn reload(&mut self) -> Result<()> {
+        self.device = devices::get_device(&self.config_dir)?;
+        self.storages = Storages::read(&self.config_dir)?;
+        self.storage_rows = self.storages.tree();
+        self.backups =
+            Backups::read_all(&self.config_dir, &devices::get_devices(&self.config_dir)?)?;
+        clamp_selection(&mut self.storage_state, self.storage_rows.len());
+        clamp_selection(&mut self.backup_state, self.backups.len());
+        let n_logs = self.selected_backup().map_or(0, |(_, b)| b.logs().len());
+        clamp_selection(&mut self.log_state, n_logs);
+        Ok(())
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Storages => (&mut self.storage_state, self.storage_rows.len()),
+            Pane::Backups => (&mut self.backup_state, self.backups.len()),
+            Pane::Logs => {
+                let n_logs = self
+                    .backup_state
+                    .selected()
+                    .and_then(|i| self.backups.get(i))
+                    .map_or(0, |(_, b)| b.logs().len());
+                (&mut self.log_state, n_logs)
+            }
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).clamp(0, len as isize - 1) as usize));
+        if self.focus == Pane::Backups {
+            // show the latest log of the newly selected backup
+            let n_logs = self.selected_backup().map_or(0, |(_, b)| b.logs().len());
+            self.log_state.select(n_logs.checked_sub(1));
+        }
+    }
+
+    fn selected_storage(&self) -> Option<&Storage> {
+        let (_, name) = self.storage_rows.get(self.storage_state.selected()?)?;
+        self.storages.get(name)
+    }
+
+    fn selected_backup(&self) -> Option<&(String, Backup)> {
+        self.backups.get(self.backup_state.selected()?)
+    }
+
+    /// Run `action` on the normal terminal.
+    fn perform(&self, action: Action) -> Result<()> {
+        let repo = Repository::open(&self.config_dir)?;
+        match action {
+            Action::Bind => {
+                let storage = self
+                    .selected_storage()
+                    .context("Select a storage in the storages pane")?;
+                println!("Bind storage {} on {}", storage.name(), self.device.name());
+                let alias = Text::new("Alias of the storage for this device:")
+                    .prompt()
+                    .context("Failed to get alias.")?;
+                let path = Text::new("Mount point on this device:")
+                    .with_autocomplete(FilePathCompleter::default())
+                    .prompt()
+                    .context("Failed to get mount point.")?;
+                cmd_storage::cmd_storage_bind(
+                    storage.name().clone(),
+                    alias,
+                    util::expand_tilde(PathBuf::from(path))?,
+                    repo,
+                    &self.config_dir,
+                )
+            }
+            Action::Done => {
+                let (device_name, backup) = self
+                    .selected_backup()
+                    .context("Select a backup in the backups pane")?;
+                if *device_name != self.device.name() {
+                    return Err(anyhow::anyhow!(
+                        "Backup {} is not on this device",
+                        backup.name()
+                    ));
+                }
+                println!("Record backup {} done", backup.name());
+                let exit_status = CustomType::<u64>::new("Exit status:")
+                    .with_default(0)
+                    .with_error_message("Please type number.")
+                    .prompt()
+                    .context("Failed to get exit status.")?;
+                let log = Text::new("Log:").prompt().context("Failed to get log.")?;
+                cmd_backup::cmd_backup_done(
+                    backup.name().clone(),
+                    exit_status,
+                    None,
+                    Some(log),
+                    None,
+                    None,
+                    repo,
+                    &self.config_dir,
+                )
+            }
+            Action::Sync => cmd_sync::cmd_sync(
+                &self.config_dir,
+                self.settings.remote.clone(),
+                self.settings.use_sshagent,
+                self.settings.ssh_key.clone(),
+                self.settings.use_libgit2,
+            ),
+            Action::Check => cmd_check::cmd_check(&self.config_dir),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, status_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [storages_area, right_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main_area);
+        let [backups_area, logs_area] =
+            Layout::vertical([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(right_area);
+
+        let storage_items: Vec<ListItem> = self
+            .storage_rows
+            .iter()
+            .filter_map(|(depth, name)| {
+                let storage = self.storages.get(name)?;
+                Some(ListItem::new(self.storage_line(*depth, storage)))
+            })
+            .collect();
+        frame.render_stateful_widget(
+            List::new(storage_items)
+                .block(self.block(Pane::Storages, "Storages"))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            storages_area,
+            &mut self.storage_state,
+        );
+
+        let backup_items: Vec<ListItem> = self
+            .backups
+            .iter()
+            .map(|(device_name, backup)| ListItem::new(backup_line(device_name, backup)))
+            .collect();
+        frame.render_stateful_widget(
+            List::new(backup_items)
+                .block(self.block(Pane::Backups, "Backups"))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            backups_area,
+            &mut self.backup_state,
+        );
+
+        let (logs_title, log_items) = match self.selected_backup() {
+            Some((_, backup)) => (
+                format!("Logs: {}", backup.name()),
+                backup
+                    .logs()
+                    .iter()
+                    .map(|log| ListItem::new(log_line(log)))
+                    .collect(),
+            ),
+            None => ("Logs".to_string(), vec![]),
+        };
+        frame.render_stateful_widget(
+            List::new(log_items)
+                .block(self.block(Pane::Logs, &logs_title))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            logs_area,
+            &mut self.log_state,
+        );
+
+        let status = if self.message.is_empty() {
+            HELP.to_string()
+        } else {
+            format!("{}  | {}", self.message, HELP)
+        };
+        frame.render_widget(
+            Paragraph::new(status).style(Style::new().add_modifier(Modifier::DIM)),
+            status_area,
+        );
+    }
+
+    fn block<'a>(&self, pane: Pane, title: &'a str) -> Block<'a> {
+        let block = Block::bordered().title(title);
+        if self.focus == pane {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+
+    fn storage_line(&self, depth: usize, storage: &Storage) -> Line<'static> {
+        let typestyle = convert_style(&storage.typestyle());
+        let mount_path = storage
+            .mount_path(&self.device)
+            .map_or("-".to_string(), |path| path.display().to_string());
+        let usage = storage.usage().and_then(|usage| usage.latest());
+        let usage_str = usage
+            .and_then(|record| record.used_percent())
+            .map_or("".to_string(), |percent| format!("{:>3.0}%", percent));
+        Line::from(vec![
+            Span::raw("  ".repeat(depth)),
+            Span::styled(storage.shorttypename().to_string(), typestyle),
+            Span::raw(" "),
+            Span::styled(storage.name().clone(), typestyle),
+            Span::raw(" "),
+            Span::styled(usage_str, convert_style(&cmd_storage::usage_style(usage))),
+            Span::raw(" "),
+            Span::styled(mount_path, Style::new().add_modifier(Modifier::DIM)),
+        ])
+    }
+}
+
+fn backup_line(device_name: &str, backup: &Backup) -> Line<'static> {
+    let (elapsed, style) = match backup.last_backup() {
+        Some(log) => {
+            let time = Local::now() - log.datetime;
+            (
+                util::format_summarized_duration(time),
+                convert_style(&util::duration_style(time)),
+            )
+        }
+        None => ("---".to_string(), Style::new().fg(Color::Red)),
+    };
+    let status = backup.last_backup().map_or(Span::raw(""), |log| {
+        Span::styled(
+            log.status().to_string(),
+            convert_style(&log.status().style()),
+        )
+    });
+    Line::from(vec![
+        Span::styled(format!("[{}]", device_name), Style::new().fg(Color::Blue)),
+        Span::raw(" "),
+        Span::styled(backup.name().clone(), style),
+        Span::raw(" "),
+        Span::styled(format!("{:>6}", elapsed), style),
+        Span::raw(" "),
+        status,
+    ])
+}
+
+fn log_line(log: &backups::BackupLog) -> Line<'static> {
+    let exit_code = log
+        .exit_code()
+        .map_or("".to_string(), |code| format!(" ({})", code));
+    Line::from(vec![
+        Span::raw(log.datetime.format("%Y-%m-%d %H:%M ").to_string()),
+        Span::styled(
+            log.status().to_string(),
+            convert_style(&log.status().style()),
+        ),
+        Span::raw(exit_code),
+        Span::raw(" "),
+        Span::styled(
+            log.log().lines().last().unwrap_or("").to_string(),
+            Style::new().add_modifier(Modifier::DIM),
+        ),
+    ])
+}
+
+fn clamp_selection(state: &mut ListState, len: usize) {
+    let selected = match state.selected() {
+        _ if len == 0 => None,
+        Some(i) => Some(i.min(len - 1)),
+        None => Some(0),
+    };
+    state.select(selected);
+}
+
+fn wait_enter() -> Result<()> {
+    print!("Press Enter to return...");
+    io::stdout().flush()?;
+    io::stdin().lock().read_line(&mut String::new())?;
+    Ok(())
+}
+
+/// Convert [`console::Style`] for the CLI output into [`Style`]
+/// by parsing its SGR sequences, so that the same colors are used in both.
+fn convert_style(style: &console::Style) -> Style {
+    let styled = style.clone().force_styling(true).apply_to("").to_string();
+    let mut converted = Style::new();
+    for sequence in styled.split('\x1b').filter_map(|s| s.strip_prefix('[')) {
+        let Some(params) = sequence.strip_suffix('m') else {
+            continue;
+        };
+        let params: Vec<u8> = params.split(';').filter_map(|p| p.parse().ok()).collect();
+        converted = match params.as_slice() {
+            [38, 5, n] => converted.fg(Color::Indexed(*n)),
+            [48, 5, n] => converted.bg(Color::Indexed(*n)),
+            [38, 2, r, g, b] => converted.fg(Color::Rgb(*r, *g, *b)),
+            [48, 2, r, g, b] => converted.bg(Color::Rgb(*r, *g, *b)),
+            [n @ 30..=37] => converted.fg(Color::Indexed(n - 30)),
+            [n @ 40..=47] => converted.bg(Color::Indexed(n - 40)),
+            [1] => converted.add_modifier(Modifier::BOLD),
+            [2] => converted.add_modifier(Modifier::DIM),
+            [3] => converted.add_modifier(Modifier::ITALIC),
+            [4] => converted.add_modifier(Modifier::UNDERLINED),
+            [5] => converted.add_modifier(Modifier::SLOW_BLINK),
+            [7] => converted.add_modifier(Modifier::REVERSED),
+            [8] => converted.add_modifier(Modifier::HIDDEN),
+            [9] => converted.add_modifier(Modifier::CROSSED_OUT),
+            _ => converted,
+        };
+    }
+    converted
+}
+
+#[cfg(test)]
+mod test {
+    use console::Style as ConsoleStyle;
+    use ratatui::style::{Color, Modifier, Style};
+
+    use super::convert_style;
+
+    #[test]
+    fn convert_console_style() {
+        assert_eq!(
+            convert_style(&ConsoleStyle::new().green()),
+            Style::new().fg(Color::Indexed(2))
+        );
+        assert_eq!(
+            convert_style(&ConsoleStyle::new().red().bold()),
+            Style::new()
+                .fg(Color::Indexed(1))
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            convert_style(&ConsoleStyle::new().on_red().black()),
+            Style::new().fg(Color::Indexed(0)).bg(Color::Indexed(1))
+        );
+        assert_eq!(
+            convert_style(&ConsoleStyle::new().bright().black()),
+            Style::new().fg(Color::Indexed(8))
+        );
+        assert_eq!(convert_style(&ConsoleStyle::new()), Style::new());
+    }
+}
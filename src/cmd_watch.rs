@@ -0,0 +1,194 @@
+//! Watch subcommand.
+//! Runs backups to a drive when it gets mounted.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use git2::Repository;
+use sysinfo::{Disk, Disks};
+
+use crate::{
+    backups::{BackupCommand, Backups},
+    cmd_backup,
+    devices::{self, Device},
+    storages::{Storage, StorageExt, Storages, physical_drive_partition},
+};
+
+pub(crate) fn cmd_watch(interval: u64, config_dir: &Path) -> Result<()> {
+    let mut mounted = mount_points(&Disks::new_with_refreshed_list());
+    println!("Watching for new drives...");
+    loop {
+        thread::sleep(Duration::from_secs(interval));
+        let disks = Disks::new_with_refreshed_list();
+        for disk in disks.list() {
+            if mounted.contains(disk.mount_point()) {
+                continue;
+            }
+            if let Err(e) = on_new_disk(disk, config_dir) {
+                eprintln!("{:#}", e);
+            }
+        }
+        mounted = mount_points(&disks);
+    }
+}
+
+fn mount_points(disks: &Disks) -> BTreeSet<PathBuf> {
+    disks
+        .list()
+        .iter()
+        .map(|disk| disk.mount_point().to_path_buf())
+        .collect()
+}
+
+/// Run backups whose destination is on the newly mounted `disk`.
+fn on_new_disk(disk: &Disk, config_dir: &Path) -> Result<()> {
+    let disk_name = disk.name().to_string_lossy();
+    let uuid = physical_drive_partition::partition_uuid(disk.name());
+    println!(
+        "[{}] Mounted {} on {}",
+        Local::now().format("%Y-%m-%d %T"),
+        disk_name,
+        disk.mount_point().display()
+    );
+    let device = devices::get_device(config_dir)?;
+    let storages = Storages::read(config_dir)?;
+    let backups = Backups::read(config_dir, &device)?;
+    for storage in matching_storages(&storages, &disk_name, uuid.as_deref(), &device) {
+        if storage.mount_path(&device).as_deref() != Some(disk.mount_point()) {
+            println!(
+                "{} is mounted on a different path. Run `xdbm storage bind {}` to update it.",
+                storage.name(),
+                storage.name()
+            );
+            continue;
+        }
+        for name in backups_to_storage(&backups, &storages, storage.name()) {
+            let backup = backups.get(&name).context("Backup not found")?;
+            if matches!(backup.command(), BackupCommand::ExternallyInvoked(_)) {
+                println!(
+                    "{}: run it manually and record with `xdbm backup done`",
+                    name
+                );
+                continue;
+            }
+            println!("{}: running", name);
+            let repo = Repository::open(config_dir).context("Failed to open the repository")?;
+            if let Err(e) = cmd_backup::cmd_backup_run(name, false, repo, config_dir, &storages) {
+                eprintln!("{:#}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Physical drive partitions matching the disk.
+fn matching_storages<'a>(
+    storages: &'a Storages,
+    disk_name: &str,
+    uuid: Option<&str>,
+    device: &Device,
+) -> Vec<&'a Storage> {
+    storages
+        .list
+        .values()
+        .filter(|storage| match storage {
+            Storage::Physical(p) => p.matches_disk(disk_name, uuid, device),
+            _ => false,
+        })
+        .collect()
+}
+
+/// Names of backups whose destination is on the storage `root`.
+fn backups_to_storage(backups: &Backups, storages: &Storages, root: &String) -> Vec<String> {
+    backups
+        .list
+        .values()
+        .filter(|backup| {
+            storages
+                .get(&backup.destination().storage)
+                .and_then(|dest| storages.root(dest))
+                .is_some_and(|dest_root| dest_root.name() == root)
+        })
+        .map(|backup| backup.name().clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{
+        backups::{Backup, BackupCommand, BackupTarget, Backups, ExternallyInvoked},
+        devices::Device,
+        storages::{
+            Storage, StorageExt, Storages, directory::Directory, local_info::LocalInfo,
+            physical_drive_partition::PhysicalDrivePartition,
+        },
+    };
+
+    use super::{backups_to_storage, matching_storages};
+
+    #[test]
+    fn find_backups_on_new_disk() {
+        let device = Device::new("dev".to_string());
+        let mut storages = Storages::new();
+        for (name, alias) in [("usb", "/dev/sdb1"), ("internal", "/dev/sda1")] {
+            storages
+                .add(Storage::Physical(PhysicalDrivePartition::new(
+                    name.to_string(),
+                    "HDD".to_string(),
+                    1_000_000,
+                    "ext4".to_string(),
+                    true,
+                    LocalInfo::new(alias.to_string(), PathBuf::from("/mnt").join(name)),
+                    &device,
+                )))
+                .unwrap();
+        }
+        let directory = Directory::try_from_device_path(
+            "usb_backup".to_string(),
+            PathBuf::from("/mnt/usb/backup"),
+            "".to_string(),
+            "".to_string(),
+            &device,
+            &storages,
+        )
+        .unwrap();
+        storages.add(Storage::SubDirectory(directory)).unwrap();
+
+        let matched = matching_storages(&storages, "/dev/sdb1", None, &device);
+        assert_eq!(
+            matched
+                .iter()
+                .map(|s| s.name().as_str())
+                .collect::<Vec<_>>(),
+            vec!["usb"]
+        );
+
+        let mut backups = Backups::new();
+        for (name, dest) in [("to_usb", "usb_backup"), ("to_internal", "internal")] {
+            backups
+                .add(Backup::new(
+                    name.to_string(),
+                    device.name(),
+                    BackupTarget::new("internal".to_string(), PathBuf::from("src")).unwrap(),
+                    BackupTarget::new(dest.to_string(), PathBuf::from("dest")).unwrap(),
+                    BackupCommand::ExternallyInvoked(ExternallyInvoked::new(
+                        "manual".to_string(),
+                        "".to_string(),
+                    )),
+                ))
+                .unwrap();
+        }
+        assert_eq!(
+            backups_to_storage(&backups, &storages, &"usb".to_string()),
+            vec!["to_usb"]
+        );
+    }
+}
@@ -13,7 +13,7 @@ pub const DEVICESFILE: &str = "devices.yml";
 /// Represents each devices.
 /// Identified by name, which is accessible from `name()`.
 /// Store os name, os version and hostname as supplemental information.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Device {
     name: String,
     os_name: String,
@@ -46,11 +46,40 @@ impl Device {
     pub fn name(&self) -> String {
         self.name.to_string()
     }
+
+    pub fn os_name(&self) -> &String {
+        &self.os_name
+    }
+
+    pub fn os_version(&self) -> &String {
+        &self.os_version
+    }
+
+    pub fn hostname(&self) -> &String {
+        &self.hostname
+    }
+
+    /// Set new name.
+    pub fn rename(&mut self, name: String) {
+        self.name = name;
+    }
+
+    /// Re-read additional data via sysinfo.
+    /// Returns `true` if anything changed.
+    pub fn refresh(&mut self) -> bool {
+        let refreshed = Device::new(self.name());
+        let changed = *self != refreshed;
+        *self = refreshed;
+        changed
+    }
 }
 
+/// File to store the name of the current device. Not tracked by git.
+pub const DEVNAMEFILE: &str = "devname";
+
 /// Get devname of the device from file `devname`.
 fn get_devname(config_dir: &Path) -> Result<String> {
-    let f = File::open(config_dir.join("devname")).context("Failed to open devname file")?;
+    let f = File::open(config_dir.join(DEVNAMEFILE)).context("Failed to open devname file")?;
     let bufreader = BufReader::new(f);
     let devname = bufreader
         .lines()
@@ -60,6 +89,14 @@ fn get_devname(config_dir: &Path) -> Result<String> {
     Ok(devname)
 }
 
+/// Save `devname` of the device to file `devname`.
+pub fn write_devname(config_dir: &Path, devname: &str) -> Result<()> {
+    let f = File::create(config_dir.join(DEVNAMEFILE))
+        .context("Failed to create a file to store local device name")?;
+    let writer = BufWriter::new(f);
+    serde_yaml::to_writer(writer, devname).map_err(|e| anyhow!(e))
+}
+
 /// Get current device.
 pub fn get_device(config_dir: &Path) -> Result<Device> {
     let devname = get_devname(config_dir)?;
@@ -0,0 +1,229 @@
+//! Disks, RAID arrays and pools which physical partitions are on.
+//!
+//! Partitions on the same disk fail together, so copies on them are not independent.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::Path,
+};
+
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::storages::{Storage, StorageExt, Storages};
+
+/// YAML file to store disks and pools.
+pub const DRIVESFILE: &str = "drives.yml";
+
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DriveKind {
+    /// Single physical disk.
+    Disk,
+    /// RAID array of member disks.
+    Raid,
+    /// Storage pool like ZFS or LVM spanning member disks.
+    Pool,
+}
+
+impl fmt::Display for DriveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DriveKind::Disk => "disk",
+            DriveKind::Raid => "raid",
+            DriveKind::Pool => "pool",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Disk or a group of disks which partitions are on.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Drive {
+    name: String,
+    pub kind: DriveKind,
+    /// Names of the member [`Drive`]s of RAID arrays and pools.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+impl Drive {
+    pub fn new(name: String, kind: DriveKind, members: Vec<String>, notes: String) -> Drive {
+        Drive {
+            name,
+            kind,
+            members,
+            notes,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Drives {
+    pub list: BTreeMap<String, Drive>,
+}
+
+impl Drives {
+    pub fn get(&self, name: &str) -> Option<&Drive> {
+        self.list.get(name)
+    }
+
+    /// Add new [`Drive`]. Its members should be added beforehand.
+    pub fn add(&mut self, drive: Drive) -> Result<()> {
+        if self.list.contains_key(drive.name()) {
+            return Err(anyhow!("Drive {} already exists", drive.name()));
+        }
+        if drive.kind == DriveKind::Disk && !drive.members.is_empty() {
+            return Err(anyhow!("Disk {} can't have members", drive.name()));
+        }
+        if let Some(member) = drive.members.iter().find(|m| !self.list.contains_key(*m)) {
+            return Err(anyhow!("No drive has name {}", member));
+        }
+        self.list.insert(drive.name.clone(), drive);
+        Ok(())
+    }
+
+    /// Names of the physical disks which the drive `name` consists of.
+    /// RAID arrays and pools without members are treated as single disks.
+    pub fn disks(&self, name: &str) -> BTreeSet<String> {
+        let mut disks = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![name.to_string()];
+        while let Some(name) = stack.pop() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            match self.list.get(&name) {
+                Some(drive) if !drive.members.is_empty() => {
+                    stack.extend(drive.members.iter().cloned())
+                }
+                _ => {
+                    disks.insert(name);
+                }
+            }
+        }
+        disks
+    }
+
+    /// Physical disks which `storage` is on.
+    /// A partition not assigned to any drive is regarded as a disk by itself.
+    /// `None` if `storage` is not on a physical partition.
+    pub fn storage_disks(
+        &self,
+        storages: &Storages,
+        storage: &Storage,
+    ) -> Option<BTreeSet<String>> {
+        let partition = storages.partition(storage)?;
+        Some(match &partition.drive {
+            Some(drive) => self.disks(drive),
+            None => BTreeSet::from([partition.name().clone()]),
+        })
+    }
+
+    /// Disks shared by the storages named `a` and `b`.
+    /// Empty if they are independent.
+    pub fn shared_disks(&self, storages: &Storages, a: &String, b: &String) -> Vec<String> {
+        let disks = |name| {
+            storages
+                .get(name)
+                .and_then(|storage| self.storage_disks(storages, storage))
+                .unwrap_or_default()
+        };
+        disks(a).intersection(&disks(b)).cloned().collect()
+    }
+
+    /// Load [`Drives`] from `config_dir`. Empty if the file doesn't exist.
+    pub fn read(config_dir: &Path) -> Result<Drives> {
+        let drives_file = config_dir.join(DRIVESFILE);
+        if !drives_file.exists() {
+            trace!("No drives file found.");
+            return Ok(Drives::default());
+        }
+        let f = fs::File::open(drives_file)?;
+        let reader = io::BufReader::new(f);
+        serde_yaml::from_reader(reader).context(format!("Failed to parse {}", DRIVESFILE))
+    }
+
+    pub fn write(&self, config_dir: &Path) -> Result<()> {
+        let f = fs::File::create(config_dir.join(DRIVESFILE))
+            .context(format!("Failed to open {}", DRIVESFILE))?;
+        let writer = io::BufWriter::new(f);
+        serde_yaml::to_writer(writer, self).context(format!("Failed to write {}", DRIVESFILE))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{Drive, DriveKind, Drives};
+
+    #[test]
+    fn pool_disks() {
+        let mut drives = Drives::default();
+        for name in ["a", "b", "c"] {
+            drives
+                .add(Drive::new(
+                    name.to_string(),
+                    DriveKind::Disk,
+                    vec![],
+                    "".to_string(),
+                ))
+                .unwrap();
+        }
+        let members = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        drives
+            .add(Drive::new(
+                "mirror".to_string(),
+                DriveKind::Raid,
+                members(&["a", "b"]),
+                "".to_string(),
+            ))
+            .unwrap();
+        drives
+            .add(Drive::new(
+                "tank".to_string(),
+                DriveKind::Pool,
+                members(&["mirror", "c"]),
+                "".to_string(),
+            ))
+            .unwrap();
+        assert!(
+            drives
+                .add(Drive::new(
+                    "bad".to_string(),
+                    DriveKind::Disk,
+                    members(&["a"]),
+                    "".to_string(),
+                ))
+                .is_err()
+        );
+        assert!(
+            drives
+                .add(Drive::new(
+                    "bad".to_string(),
+                    DriveKind::Pool,
+                    members(&["missing"]),
+                    "".to_string(),
+                ))
+                .is_err()
+        );
+        assert_eq!(
+            drives.disks("tank"),
+            BTreeSet::from(["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+        assert_eq!(drives.disks("c"), BTreeSet::from(["c".to_string()]));
+        assert_eq!(
+            drives.disks("unknown"),
+            BTreeSet::from(["unknown".to_string()])
+        );
+    }
+}
@@ -1,8 +1,15 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
-use git2::{Cred, RemoteCallbacks};
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeZone};
+use git2::{Cred, RemoteCallbacks, Repository};
 use inquire::Password;
 
+use crate::{backups, devices::Device};
+
 pub(crate) fn get_credential<'a>(
     use_sshagent: bool,
     ssh_key: Option<PathBuf>,
@@ -38,3 +45,53 @@ pub(crate) fn get_credential<'a>(
     });
     callbacks
 }
+
+/// Find the datetime of the latest commit which changed backups or logs of each device.
+/// Devices without such commits are not contained.
+pub(crate) fn last_device_commits(
+    repo: &Repository,
+    devices: &[Device],
+) -> Result<BTreeMap<String, DateTime<Local>>> {
+    let mut found = BTreeMap::new();
+    if repo.head().is_err() {
+        return Ok(found);
+    }
+    let targets: Vec<(String, PathBuf, PathBuf)> = devices
+        .iter()
+        .map(|device| {
+            (
+                device.name(),
+                backups::backups_file(device),
+                PathBuf::from(backups::LOGSDIR).join(device.name()),
+            )
+        })
+        .collect();
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    revwalk.push_head()?;
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        let Some(datetime) = Local.timestamp_opt(commit.time().seconds(), 0).single() else {
+            continue;
+        };
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                continue;
+            };
+            for (name, backups_file, logs_dir) in &targets {
+                if path == backups_file || path.starts_with(logs_dir) {
+                    found.entry(name.clone()).or_insert(datetime);
+                }
+            }
+        }
+        if found.len() == targets.len() {
+            break;
+        }
+    }
+    Ok(found)
+}
@@ -0,0 +1,97 @@
+//! Commands executed before and after backups.
+
+use std::{
+    fs, io,
+    path::Path,
+    process::{self, ExitStatus},
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// YAML file to store hooks applied to all backups.
+pub const HOOKSFILE: &str = "hooks.yml";
+
+/// Shell commands run before and after the backup.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Hooks {
+    /// Run before the backup. The backup is aborted if any of them fails.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<String>,
+    /// Run after the backup, regardless of the result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<String>,
+}
+
+impl Hooks {
+    pub fn new(pre: Vec<String>, post: Vec<String>) -> Hooks {
+        Hooks { pre, post }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+
+    /// Load global [`Hooks`] from data in `config_dir`.
+    /// Empty [`Hooks`] if the file doesn't exist.
+    pub fn read_global(config_dir: &Path) -> Result<Hooks> {
+        let hooks_file = config_dir.join(HOOKSFILE);
+        if !hooks_file.exists() {
+            trace!("No global hooks file found.");
+            return Ok(Hooks::default());
+        }
+        let f = fs::File::open(hooks_file)?;
+        let reader = io::BufReader::new(f);
+        serde_yaml::from_reader(reader).context(format!("Failed to parse {}", HOOKSFILE))
+    }
+}
+
+/// Construct [`process::Command`] which executes `cmd` in the shell.
+pub fn shell_command(cmd: &str) -> process::Command {
+    if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        command.arg("/C").arg(cmd);
+        command
+    } else {
+        let mut command = process::Command::new("sh");
+        command.arg("-c").arg(cmd);
+        command
+    }
+}
+
+/// Run `hooks` in order with environment variables `envs`.
+/// Stops at the first failed hook and returns error.
+pub fn run_hooks(hooks: &[String], envs: &[(&str, String)]) -> Result<()> {
+    for hook in hooks {
+        debug!("Running hook: {}", hook);
+        let status: ExitStatus = shell_command(hook)
+            .envs(envs.iter().map(|(k, v)| (k, v)))
+            .status()
+            .context(format!("Failed to execute hook `{}`", hook))?;
+        if !status.success() {
+            return Err(anyhow!("Hook `{}` failed with {}", hook, status));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::run_hooks;
+
+    #[test]
+    #[cfg(unix)]
+    fn hooks_get_env() {
+        let envs = [("XDBM_BACKUP_NAME", "foo".to_string())];
+        assert!(run_hooks(&["test \"$XDBM_BACKUP_NAME\" = foo".to_string()], &envs).is_ok());
+        let err = run_hooks(
+            &[
+                "true".to_string(),
+                "test \"$XDBM_BACKUP_NAME\" = bar".to_string(),
+            ],
+            &envs,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("bar"));
+    }
+}
@@ -16,28 +16,50 @@ extern crate log;
 extern crate dirs;
 
 use anyhow::{Context, Result};
+use backups::retention::LogRetention;
 use clap::{CommandFactory, Parser};
 use clap_complete::CompleteEnv;
 use git2::{Commit, Oid, Repository};
 use std::path::Path;
 use std::path::{self, PathBuf};
 use storages::Storages;
+use storages::physical_drive_partition::{DriveInfo, DriveStatus};
 
-use crate::cmd_args::{BackupSubCommands, Cli, Commands, StorageCommands};
+use crate::cmd_args::{
+    BackupSubCommands, Cli, Commands, DeviceSubCommands, DriveSubCommands, ScheduleSubCommands,
+    StorageCommands,
+};
 use devices::{DEVICESFILE, Device};
+use hooks::Hooks;
+use settings::Settings;
 
 mod backups;
 mod cmd_args;
 mod cmd_backup;
 mod cmd_check;
 mod cmd_completion;
+mod cmd_daemon;
+mod cmd_device;
+mod cmd_drive;
 mod cmd_init;
+mod cmd_metrics;
+mod cmd_migrate;
+mod cmd_notify;
+mod cmd_report;
+mod cmd_schedule;
 mod cmd_status;
 mod cmd_storage;
 mod cmd_sync;
+mod cmd_tui;
+mod cmd_watch;
 mod devices;
+mod drives;
 mod git;
+mod hooks;
 mod inquire_filepath_completer;
+mod notify;
+mod preflight;
+mod settings;
 mod storages;
 mod util;
 
@@ -56,6 +78,10 @@ fn main() -> Result<()> {
         None => default_config_dir()?,
     };
     trace!("Config dir: {:?}", config_dir);
+    let settings = Settings::read(&config_dir)?;
+    trace!("Settings: {:?}", settings);
+    settings.apply_color();
+    let commits = cli.command.commits();
 
     match cli.command {
         Commands::Init {
@@ -73,7 +99,9 @@ fn main() -> Result<()> {
                 StorageCommands::Add(storageargs) => {
                     cmd_storage::cmd_storage_add(storageargs.command, repo, &config_dir)?
                 }
-                StorageCommands::List { long } => cmd_storage::cmd_storage_list(&config_dir, long)?,
+                StorageCommands::List { long } => {
+                    cmd_storage::cmd_storage_list(&config_dir, long || settings.long)?
+                }
                 StorageCommands::Bind {
                     storage: storage_name,
                     alias: new_alias,
@@ -85,6 +113,58 @@ fn main() -> Result<()> {
                     repo,
                     &config_dir,
                 )?,
+                StorageCommands::Refresh {} => cmd_storage::cmd_storage_refresh(repo, &config_dir)?,
+                StorageCommands::Move {
+                    storage: storage_name,
+                    location,
+                    offsite,
+                } => cmd_storage::cmd_storage_move(
+                    storage_name,
+                    location,
+                    offsite,
+                    repo,
+                    &config_dir,
+                )?,
+                StorageCommands::Edit {
+                    storage: storage_name,
+                    serial,
+                    model,
+                    purchased,
+                    warranty_end,
+                    status,
+                    drive,
+                } => cmd_storage::cmd_storage_edit(
+                    storage_name,
+                    DriveInfo {
+                        serial,
+                        model,
+                        purchased,
+                        warranty_end,
+                        status,
+                        drive,
+                    },
+                    repo,
+                    &config_dir,
+                )?,
+                StorageCommands::Retire {
+                    storage: storage_name,
+                    failed,
+                } => {
+                    let status = if failed {
+                        DriveStatus::Failed
+                    } else {
+                        DriveStatus::Retired
+                    };
+                    cmd_storage::cmd_storage_edit(
+                        storage_name,
+                        DriveInfo {
+                            status: Some(status),
+                            ..Default::default()
+                        },
+                        repo,
+                        &config_dir,
+                    )?
+                }
             }
         }
         Commands::Path {} => {
@@ -93,15 +173,84 @@ fn main() -> Result<()> {
         Commands::Sync {
             remote_name,
             use_libgit2,
+            no_libgit2,
             use_sshagent,
+            no_sshagent,
             ssh_key,
-        } => cmd_sync::cmd_sync(&config_dir, remote_name, use_sshagent, ssh_key, use_libgit2)?,
+        } => cmd_sync::cmd_sync(
+            &config_dir,
+            remote_name.or(settings.remote.clone()),
+            settings::flag(use_sshagent, no_sshagent, settings.use_sshagent),
+            ssh_key.or(settings.ssh_key.clone()),
+            settings::flag(use_libgit2, no_libgit2, settings.use_libgit2),
+        )?,
         Commands::Status {
             path,
             storage,
             backup,
-        } => cmd_status::cmd_status(path, storage, backup, &config_dir)?,
+            copies,
+        } => cmd_status::cmd_status(path, storage, backup, copies, &config_dir)?,
         Commands::Check {} => cmd_check::cmd_check(&config_dir)?,
+        Commands::Tui {} => cmd_tui::cmd_tui(&config_dir, settings.clone())?,
+        Commands::Report { html } => cmd_report::cmd_report(html, &config_dir)?,
+        Commands::Metrics { prometheus } => cmd_metrics::cmd_metrics(prometheus, &config_dir)?,
+        Commands::Notify { dry_run } => cmd_notify::cmd_notify(dry_run, &config_dir)?,
+        Commands::Daemon {
+            interval,
+            once,
+            sync,
+        } => cmd_daemon::cmd_daemon(
+            interval,
+            once,
+            sync || settings.auto_sync,
+            &config_dir,
+            &settings,
+        )?,
+        Commands::Watch { interval } => cmd_watch::cmd_watch(interval, &config_dir)?,
+        Commands::Schedule(schedule) => match schedule {
+            ScheduleSubCommands::Export {
+                systemd,
+                cron: _,
+                output,
+            } => cmd_schedule::cmd_schedule_export(systemd, output, &config_dir)?,
+        },
+        Commands::Device(device) => {
+            trace!("device subcommand with args: {:?}", device);
+            let repo = Repository::open(&config_dir).context(
+                "Repository doesn't exist on the config path. Please run init to initialize the repository.",
+            )?;
+            match device {
+                DeviceSubCommands::List {} => cmd_device::cmd_device_list(repo, &config_dir)?,
+                DeviceSubCommands::Rename { old_name, new_name } => {
+                    cmd_device::cmd_device_rename(old_name, new_name, repo, &config_dir)?
+                }
+                DeviceSubCommands::Remove { name, cascade } => {
+                    cmd_device::cmd_device_remove(name, cascade, repo, &config_dir)?
+                }
+                DeviceSubCommands::Refresh {} => cmd_device::cmd_device_refresh(repo, &config_dir)?,
+            }
+        }
+        Commands::Drive(drive) => {
+            trace!("drive subcommand with args: {:?}", drive);
+            let repo = Repository::open(&config_dir).context(
+                "Repository doesn't exist on the config path. Please run init to initialize the repository.",
+            )?;
+            match drive {
+                DriveSubCommands::Add {
+                    name,
+                    kind,
+                    members,
+                    notes,
+                } => cmd_drive::cmd_drive_add(name, kind, members, notes, repo, &config_dir)?,
+                DriveSubCommands::List {} => cmd_drive::cmd_drive_list(&config_dir)?,
+            }
+        }
+        Commands::Migrate {} => {
+            let repo = Repository::open(&config_dir).context(
+                "Repository doesn't exist on the config path. Please run init to initialize the repository.",
+            )?;
+            cmd_migrate::cmd_migrate(repo, &config_dir)?
+        }
         Commands::Backup(backup) => {
             trace!("backup subcommand with args: {:?}", backup);
             let repo = Repository::open(&config_dir).context(
@@ -113,26 +262,102 @@ fn main() -> Result<()> {
                     name,
                     src,
                     dest,
+                    dest_storage,
+                    force,
+                    pre_hook,
+                    post_hook,
+                    exit_code,
+                    schedule,
                     cmd,
-                } => {
-                    cmd_backup::cmd_backup_add(name, src, dest, cmd, repo, &config_dir, &storages)?
-                }
+                } => cmd_backup::cmd_backup_add(
+                    name,
+                    src,
+                    dest,
+                    dest_storage,
+                    force,
+                    Hooks::new(pre_hook, post_hook),
+                    exit_code.into_iter().collect(),
+                    schedule,
+                    cmd,
+                    repo,
+                    &config_dir,
+                    &storages,
+                )?,
                 BackupSubCommands::List {
                     src,
                     dest,
                     device,
                     long,
-                } => cmd_backup::cmd_backup_list(src, dest, device, long, &config_dir, &storages)?,
+                } => cmd_backup::cmd_backup_list(
+                    src,
+                    dest,
+                    device,
+                    long || settings.long,
+                    &config_dir,
+                    &storages,
+                )?,
                 BackupSubCommands::Done {
                     name,
                     exit_status,
+                    status,
+                    log,
+                    duration,
+                    bytes,
+                } => cmd_backup::cmd_backup_done(
+                    name,
+                    exit_status,
+                    status,
                     log,
-                } => cmd_backup::cmd_backup_done(name, exit_status, log, repo, &config_dir)?,
+                    duration,
+                    bytes,
+                    repo,
+                    &config_dir,
+                )?,
+                BackupSubCommands::Run { name, force } => {
+                    cmd_backup::cmd_backup_run(name, force, repo, &config_dir, &storages)?
+                }
+                BackupSubCommands::Retention {
+                    name,
+                    keep_last,
+                    max_age_days,
+                } => cmd_backup::cmd_backup_retention(
+                    name,
+                    LogRetention::new(keep_last, max_age_days),
+                    repo,
+                    &config_dir,
+                )?,
+                BackupSubCommands::Schedule { name, schedule } => {
+                    cmd_backup::cmd_backup_schedule(name, schedule, repo, &config_dir)?
+                }
+                BackupSubCommands::PruneLogs {
+                    name,
+                    keep_last,
+                    max_age_days,
+                } => cmd_backup::cmd_backup_prune_logs(
+                    name,
+                    LogRetention::new(keep_last, max_age_days),
+                    repo,
+                    &config_dir,
+                )?,
+                BackupSubCommands::Preflight { name } => {
+                    cmd_backup::cmd_backup_preflight(name, &config_dir, &storages)?
+                }
             }
         }
         Commands::Completion { shell } => cmd_completion::cmd_completion(shell)?,
     }
     full_status(&Repository::open(&config_dir)?)?;
+    if commits && settings.auto_sync {
+        info!("Auto sync");
+        cmd_sync::cmd_sync(
+            &config_dir,
+            settings.remote,
+            settings.use_sshagent,
+            settings.ssh_key,
+            settings.use_libgit2,
+        )
+        .context("Auto sync failed. Changes are committed locally.")?;
+    }
     Ok(())
 }
 
@@ -158,10 +383,27 @@ fn find_last_commit(repo: &'_ Repository) -> Result<Option<Commit<'_>>, git2::Er
 
 /// Add file and commit
 fn add_and_commit(repo: &Repository, path: &Path, message: &str) -> Result<Oid, git2::Error> {
+    add_and_commit_paths(repo, &[path.to_path_buf()], message)
+}
+
+/// Add files and commit them at once.
+/// Files which don't exist in the working directory are removed from the index.
+fn add_and_commit_paths(
+    repo: &Repository,
+    paths: &[PathBuf],
+    message: &str,
+) -> Result<Oid, git2::Error> {
     trace!("repo state: {:?}", repo.state());
     full_status(repo).unwrap();
     let mut index = repo.index()?;
-    index.add_path(path)?;
+    let workdir = repo.workdir().map(|p| p.to_path_buf()).unwrap_or_default();
+    for path in paths {
+        if workdir.join(path).exists() {
+            index.add_path(path)?;
+        } else {
+            index.remove_path(path)?;
+        }
+    }
     full_status(repo).unwrap();
     index.write()?;
     let oid = index.write_tree()?;
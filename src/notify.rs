@@ -0,0 +1,344 @@
+//! Notifications of failed or overdue backups.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::{self, Stdio},
+};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backups::{Backup, BackupLog, BackupResult},
+    hooks, util,
+};
+
+/// YAML file to configure notifications.
+pub const NOTIFYFILE: &str = "notify.yml";
+
+/// Where notifications are delivered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Sink {
+    /// Shell command which gets the message from stdin and `XDBM_NOTIFY_*` environment variables.
+    Command { command: String },
+    /// Mail sent by a sendmail compatible binary.
+    Sendmail {
+        to: String,
+        #[serde(default = "default_sendmail")]
+        sendmail: String,
+    },
+    /// HTTP POST of a JSON object to `url`.
+    Webhook { url: String },
+}
+
+fn default_sendmail() -> String {
+    "sendmail".to_string()
+}
+
+/// Notification settings shared among the devices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Backups whose last successful run is older than this are overdue.
+    pub overdue_days: i64,
+    /// Also notify successful runs of `backup done` and `backup run`.
+    pub notify_success: bool,
+    /// Template of the subject.
+    pub subject: String,
+    /// Template of the message body.
+    pub message: String,
+    pub sinks: Vec<Sink>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        NotifyConfig {
+            overdue_days: 7,
+            notify_success: false,
+            subject: "[xdbm] {name}: {reason}".to_string(),
+            message: "Backup {name} on {device}: {reason}\nstatus: {status}\nlast run: {age} ago\n\n{log}\n"
+                .to_string(),
+            sinks: vec![],
+        }
+    }
+}
+
+impl NotifyConfig {
+    /// Load [`NotifyConfig`] from data in `config_dir`.
+    /// `None` if the file doesn't exist.
+    pub fn read(config_dir: &Path) -> Result<Option<NotifyConfig>> {
+        let notify_file = config_dir.join(NOTIFYFILE);
+        if !notify_file.exists() {
+            trace!("No notify file found.");
+            return Ok(None);
+        }
+        let f = fs::File::open(notify_file)?;
+        let reader = io::BufReader::new(f);
+        serde_yaml::from_reader(reader)
+            .map(Some)
+            .context(format!("Failed to parse {}", NOTIFYFILE))
+    }
+}
+
+/// State of a backup to be notified.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub name: String,
+    pub device: String,
+    pub reason: String,
+    pub status: Option<BackupResult>,
+    /// Time elapsed since the last run.
+    pub age: Option<TimeDelta>,
+    pub log: String,
+}
+
+impl Notification {
+    /// Notification of a new `log` of a backup.
+    pub fn from_log(name: &str, device: &str, log: &BackupLog) -> Notification {
+        Notification {
+            name: name.to_string(),
+            device: device.to_string(),
+            reason: if log.status().is_ok() {
+                "completed".to_string()
+            } else {
+                format!("finished with {}", log.status())
+            },
+            status: Some(*log.status()),
+            age: Some(Local::now() - log.datetime),
+            log: log.log().clone(),
+        }
+    }
+
+    /// Notification of `backup` if it failed last time or is overdue.
+    pub fn check(backup: &Backup, device: &str, overdue_days: i64) -> Option<Notification> {
+        let last = backup.last_backup();
+        let last_ok = backup.last_success();
+        let reason = match (last, last_ok) {
+            (None, _) => "never run".to_string(),
+            (Some(last), _) if !last.status().is_ok() => {
+                format!("finished with {}", last.status())
+            }
+            (_, Some(last_ok))
+                if Local::now() - last_ok.datetime > TimeDelta::days(overdue_days) =>
+            {
+                format!(
+                    "overdue, last succeeded {} ago",
+                    util::format_summarized_duration(Local::now() - last_ok.datetime)
+                )
+            }
+            _ => return None,
+        };
+        Some(Notification {
+            name: backup.name().clone(),
+            device: device.to_string(),
+            reason,
+            status: last.map(|log| *log.status()),
+            age: last.map(|log| Local::now() - log.datetime),
+            log: last.map_or("".to_string(), |log| log.log().clone()),
+        })
+    }
+
+    fn status_str(&self) -> String {
+        self.status.map_or("-".to_string(), |s| s.to_string())
+    }
+
+    fn age_str(&self) -> String {
+        self.age
+            .map_or("-".to_string(), util::format_summarized_duration)
+    }
+
+    /// Replace `{name}`, `{device}`, `{reason}`, `{status}`, `{age}` and `{log}` in `template`.
+    /// Replaced values are not expanded again, and unknown placeholders are kept as they are.
+    pub fn render(&self, template: &str) -> String {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            let value = match &rest[1..end] {
+                "name" => self.name.clone(),
+                "device" => self.device.clone(),
+                "reason" => self.reason.clone(),
+                "status" => self.status_str(),
+                "age" => self.age_str(),
+                "log" => self.log.clone(),
+                // may be the start of a placeholder like `{{name}`
+                _ => {
+                    rendered.push('{');
+                    rest = &rest[1..];
+                    continue;
+                }
+            };
+            rendered.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+/// Send `notification` to all sinks in `config`.
+/// Continues on failure and returns the first error.
+pub fn send(config: &NotifyConfig, notification: &Notification) -> Result<()> {
+    // multi-line values must not break the mail header
+    let subject = notification
+        .render(&config.subject)
+        .replace(['\r', '\n'], " ");
+    let message = notification.render(&config.message);
+    let mut result = Ok(());
+    for sink in &config.sinks {
+        debug!("Sending notification to {:?}", sink);
+        if let Err(e) = send_to_sink(sink, notification, &subject, &message) {
+            eprintln!(
+                "{} {:#}",
+                console::style("Failed to send notification:").red().bold(),
+                e
+            );
+            if result.is_ok() {
+                result = Err(e);
+            }
+        }
+    }
+    result
+}
+
+/// Notify the result of `backup done` or `backup run` if configured.
+/// Failures are only reported and never abort the command.
+pub fn notify_result(config_dir: &Path, notification: &Notification) {
+    let config = match NotifyConfig::read(config_dir) {
+        Ok(Some(config)) => config,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            return;
+        }
+    };
+    if notification.status.is_some_and(|s| s.is_ok()) && !config.notify_success {
+        return;
+    }
+    let _ = send(&config, notification);
+}
+
+fn send_to_sink(
+    sink: &Sink,
+    notification: &Notification,
+    subject: &str,
+    message: &str,
+) -> Result<()> {
+    match sink {
+        Sink::Command { command } => {
+            let envs = [
+                ("XDBM_NOTIFY_SUBJECT", subject.to_string()),
+                ("XDBM_NOTIFY_MESSAGE", message.to_string()),
+                ("XDBM_BACKUP_NAME", notification.name.clone()),
+                ("XDBM_BACKUP_STATUS", notification.status_str()),
+            ];
+            let mut cmd = hooks::shell_command(command);
+            cmd.envs(envs);
+            pipe_to(cmd, message, command)
+        }
+        Sink::Sendmail { to, sendmail } => {
+            let mut cmd = process::Command::new(sendmail);
+            cmd.args(["-i", "--", to]);
+            let mail = format!("To: {}\nSubject: {}\n\n{}", to, subject, message);
+            pipe_to(cmd, &mail, sendmail)
+        }
+        Sink::Webhook { url } => {
+            let body = serde_json::json!({
+                "subject": subject,
+                "message": message,
+                "backup": notification.name,
+                "device": notification.device,
+                "reason": notification.reason,
+                "status": notification.status,
+                "age_seconds": notification.age.map(|age| age.num_seconds()),
+            });
+            ureq::post(url)
+                .header("Content-Type", "application/json")
+                .send(body.to_string())
+                .context(format!("Failed to post to {}", url))?;
+            Ok(())
+        }
+    }
+}
+
+/// Run `cmd` with `input` written to its stdin.
+fn pipe_to(mut cmd: process::Command, input: &str, name: &str) -> Result<()> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .spawn()
+        .context(format!("Failed to execute `{}`", name))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("`{}` failed with {}", name, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeDelta;
+
+    use crate::backups::BackupResult;
+
+    use super::{Notification, NotifyConfig, Sink};
+
+    #[test]
+    fn render_template() {
+        let notification = Notification {
+            name: "photos".to_string(),
+            device: "laptop".to_string(),
+            reason: "finished with Failure".to_string(),
+            status: Some(BackupResult::Failure),
+            age: Some(TimeDelta::days(3)),
+            log: "rsync error".to_string(),
+        };
+        assert_eq!(
+            notification.render("{name}@{device}: {status} {age} ago ({log})"),
+            "photos@laptop: Failure 3d ago (rsync error)"
+        );
+
+        // values are not expanded again
+        let notification = Notification {
+            name: "{log}".to_string(),
+            reason: "missing {status}".to_string(),
+            ..notification
+        };
+        assert_eq!(
+            notification.render("{{name}: {reason} {unknown} {log"),
+            "{{log}: missing {status} {unknown} {log"
+        );
+    }
+
+    #[test]
+    fn parse_config() {
+        let config: NotifyConfig = serde_yaml::from_str(
+            "overdue_days: 14\nsinks:\n- type: sendmail\n  to: me@example.com\n- type: webhook\n  url: http://localhost:8080/\n",
+        )
+        .unwrap();
+        assert_eq!(config.overdue_days, 14);
+        assert_eq!(
+            config.sinks,
+            vec![
+                Sink::Sendmail {
+                    to: "me@example.com".to_string(),
+                    sendmail: "sendmail".to_string()
+                },
+                Sink::Webhook {
+                    url: "http://localhost:8080/".to_string()
+                },
+            ]
+        );
+        assert!(config.subject.contains("{name}"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Checks performed before recording or running backups.
+
+use std::{fmt, fs, io, path::Path};
+
+use anyhow::{Context, Result, anyhow};
+use byte_unit::{Byte, UnitType};
+use sysinfo::Disks;
+
+use crate::{
+    backups::Backup,
+    devices::Device,
+    storages::{Storage, StorageExt, Storages, usage},
+};
+
+/// Ratio of the free space which should be left after the backup.
+/// Below this, [`CapacityCheck::Tight`] is returned.
+const FREE_SPACE_MARGIN: f64 = 0.05;
+
+/// Result of [`check_capacity`].
+#[derive(Debug)]
+pub struct Capacity {
+    /// Size of the source in bytes.
+    src_size: u64,
+    pub check: CapacityCheck,
+}
+
+impl Capacity {
+    /// Size of the source in bytes, including data already at the destination.
+    pub fn src_size(&self) -> u64 {
+        self.src_size
+    }
+}
+
+impl fmt::Display for Capacity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.check.fmt(f)
+    }
+}
+
+/// Free space of the destination compared with the bytes to be written.
+#[derive(Debug, PartialEq)]
+pub enum CapacityCheck {
+    /// Enough free space on the destination.
+    Sufficient { required: u64, free: u64 },
+    /// The backup fits, but little space will be left.
+    Tight { required: u64, free: u64 },
+    /// The backup doesn't fit in the destination.
+    Insufficient { required: u64, free: u64 },
+    /// Free space of the destination is unknown.
+    Unknown { required: u64 },
+}
+
+impl CapacityCheck {
+    fn new(required: u64, free: u64, total: u64) -> Self {
+        if required > free {
+            CapacityCheck::Insufficient { required, free }
+        } else if ((free - required) as f64) < total as f64 * FREE_SPACE_MARGIN {
+            CapacityCheck::Tight { required, free }
+        } else {
+            CapacityCheck::Sufficient { required, free }
+        }
+    }
+}
+
+impl fmt::Display for CapacityCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = |b: &u64| Byte::from_u64(*b).get_appropriate_unit(UnitType::Binary);
+        match self {
+            CapacityCheck::Sufficient { required, free } => write!(
+                f,
+                "{:.1} required, {:.1} free",
+                bytes(required),
+                bytes(free)
+            ),
+            CapacityCheck::Tight { required, free } => write!(
+                f,
+                "{:.1} required, only {:.1} free",
+                bytes(required),
+                bytes(free)
+            ),
+            CapacityCheck::Insufficient { required, free } => write!(
+                f,
+                "{:.1} required, but {:.1} free",
+                bytes(required),
+                bytes(free)
+            ),
+            CapacityCheck::Unknown { required } => write!(
+                f,
+                "{:.1} required, free space of the destination is unknown",
+                bytes(required)
+            ),
+        }
+    }
+}
+
+/// Total size of files under `path` in bytes.
+/// Symbolic links are not followed and unreadable entries are skipped.
+pub fn dir_size(path: &Path) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path)
+        .context(format!("Failed to get metadata of {}", path.display()))?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        match dir_size(&entry.path()) {
+            Ok(s) => size += s,
+            Err(e) => match e.downcast_ref::<io::Error>() {
+                Some(io_err) if io_err.kind() == io::ErrorKind::PermissionDenied => {
+                    warn!("Skipping {}: {}", entry.path().display(), io_err)
+                }
+                _ => return Err(e),
+            },
+        }
+    }
+    Ok(size)
+}
+
+/// Get the current free and total bytes of the `storage`.
+/// Measured value is used if it is mounted on `device` or is an rclone remote,
+/// otherwise the last record from `storage refresh` is used.
+fn free_space(storage: &Storage, device: &Device) -> Option<(u64, u64)> {
+    if let Storage::Rclone(remote) = storage {
+        match remote.about() {
+            Ok(record) => return Some((record.free, record.total())),
+            Err(e) => warn!("Failed to get usage of {}: {:#}", remote.name(), e),
+        }
+    }
+    if let Some(mount_path) = storage.mount_path(device) {
+        let disks = Disks::new_with_refreshed_list();
+        let exact = matches!(storage, Storage::Physical(_));
+        if let Some(disk) = usage::find_disk(&disks, &mount_path, exact) {
+            let record = usage::measure(disk);
+            return Some((record.free, record.total()));
+        }
+    }
+    let latest = storage.usage()?.latest()?;
+    Some((latest.free, latest.total()))
+}
+
+/// Compare the size of the backup source with the free space of the destination.
+/// Data already at the destination is considered to be overwritten.
+/// `dest` is `None` if the destination has no local path, like rclone remotes and archives.
+pub fn check_capacity(
+    src: &Path,
+    dest: Option<&Path>,
+    dest_storage: &Storage,
+    storages: &Storages,
+    device: &Device,
+) -> Result<Capacity> {
+    let src_size = dir_size(src)?;
+    let dest_size = match dest {
+        Some(dest) if dest.exists() => dir_size(dest)?,
+        _ => 0,
+    };
+    let required = src_size.saturating_sub(dest_size);
+    trace!("src size: {src_size}, dest size: {dest_size}");
+    let root = storages
+        .root(dest_storage)
+        .context("Failed to get the root storage of the destination")?;
+    trace!("root storage of the destination: {}", root.name());
+    let check = match free_space(root, device) {
+        Some((free, total)) => CapacityCheck::new(required, free, total),
+        None => CapacityCheck::Unknown { required },
+    };
+    Ok(Capacity { src_size, check })
+}
+
+/// [`check_capacity`] for the configured `backup`.
+pub fn check_backup_capacity(
+    backup: &Backup,
+    storages: &Storages,
+    device: &Device,
+) -> Result<Capacity> {
+    let src = backup
+        .source()
+        .path(storages, device)
+        .context("Source of the backup is not available on this device")?;
+    let dest_storage = storages
+        .get(&backup.destination().storage)
+        .context("Destination storage doesn't exist")?;
+    if backup.destination().location(storages, device).is_none() {
+        return Err(anyhow!(
+            "Destination of the backup is not available on this device"
+        ));
+    }
+    let dest = backup.destination().path(storages, device);
+    check_capacity(&src, dest.as_deref(), dest_storage, storages, device)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use anyhow::Result;
+
+    use super::{CapacityCheck, dir_size};
+
+    #[test]
+    fn capacity_check() {
+        assert_eq!(
+            CapacityCheck::new(200, 100, 1000),
+            CapacityCheck::Insufficient {
+                required: 200,
+                free: 100
+            }
+        );
+        assert_eq!(
+            CapacityCheck::new(90, 100, 1000),
+            CapacityCheck::Tight {
+                required: 90,
+                free: 100
+            }
+        );
+        assert_eq!(
+            CapacityCheck::new(10, 100, 1000),
+            CapacityCheck::Sufficient {
+                required: 10,
+                free: 100
+            }
+        );
+    }
+
+    #[test]
+    fn test_dir_size() -> Result<()> {
+        let dir = assert_fs::TempDir::new()?;
+        fs::create_dir_all(dir.path().join("a/b"))?;
+        fs::write(dir.path().join("a/foo"), [0u8; 100])?;
+        fs::write(dir.path().join("a/b/bar"), [0u8; 23])?;
+        assert_eq!(dir_size(dir.path())?, 123);
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+//! Per-device settings which are not shared via git.
+
+use std::{fs, io, path::Path, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// YAML file to store settings of this device. Not tracked by git.
+pub const SETTINGSFILE: &str = "settings.local.yml";
+
+/// When to use colored output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Detect from the terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Preferences of this device, used as defaults of the CLI flags.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Remote name used by `sync`.
+    pub remote: Option<String>,
+    /// Use custom git implementation in `sync`.
+    pub use_libgit2: bool,
+    /// Use ssh-agent in `sync`.
+    pub use_sshagent: bool,
+    /// SSH key used in `sync`.
+    pub ssh_key: Option<PathBuf>,
+    /// Colored output.
+    pub color: ColorChoice,
+    /// Use long format in `storage list` and `backup list`.
+    pub long: bool,
+    /// Run `sync` after commands which commit changes.
+    pub auto_sync: bool,
+}
+
+impl Settings {
+    /// Load [`Settings`] from data in `config_dir`.
+    /// Default [`Settings`] if the file doesn't exist.
+    pub fn read(config_dir: &Path) -> Result<Settings> {
+        let settings_file = config_dir.join(SETTINGSFILE);
+        if !settings_file.exists() {
+            trace!("No settings file found.");
+            return Ok(Settings::default());
+        }
+        let f = fs::File::open(settings_file)?;
+        let reader = io::BufReader::new(f);
+        serde_yaml::from_reader(reader).context(format!("Failed to parse {}", SETTINGSFILE))
+    }
+
+    /// Apply [`Settings::color`] to the terminal output.
+    pub fn apply_color(&self) {
+        match self.color {
+            ColorChoice::Auto => {}
+            ColorChoice::Always => {
+                console::set_colors_enabled(true);
+                console::set_colors_enabled_stderr(true);
+            }
+            ColorChoice::Never => {
+                console::set_colors_enabled(false);
+                console::set_colors_enabled_stderr(false);
+            }
+        }
+    }
+}
+
+/// Value of a CLI flag `--x` with its counterpart `--no-x`.
+/// Falls back to `default` from the settings if neither is given.
+pub fn flag(enabled: bool, disabled: bool, default: bool) -> bool {
+    match (enabled, disabled) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ColorChoice, Settings, flag};
+
+    #[test]
+    fn parse_partial_settings() {
+        let settings: Settings = serde_yaml::from_str("color: never\nauto_sync: true\n").unwrap();
+        assert_eq!(settings.color, ColorChoice::Never);
+        assert!(settings.auto_sync);
+        assert!(!settings.use_libgit2);
+        assert!(settings.remote.is_none());
+    }
+
+    #[test]
+    fn flag_overrides_settings() {
+        assert!(flag(false, false, true));
+        assert!(!flag(false, true, true));
+        assert!(flag(true, false, false));
+        assert!(!flag(false, false, false));
+    }
+}
@@ -2,8 +2,9 @@
 
 use crate::devices;
 use crate::storages::{
-    directory::Directory, online_storage::OnlineStorage,
-    physical_drive_partition::PhysicalDrivePartition,
+    archive::Archive, directory::Directory, network_share::NetworkShare,
+    offline_media::OfflineMedia, online_storage::OnlineStorage,
+    physical_drive_partition::PhysicalDrivePartition, rclone_remote::RcloneRemote,
 };
 use anyhow::{anyhow, Context, Result};
 use clap::ValueEnum;
@@ -23,6 +24,14 @@ pub enum StorageType {
     S,
     /// Online storage
     O,
+    /// Network share
+    N,
+    /// rclone remote
+    R,
+    /// Archive or repository
+    A,
+    /// Offline media
+    M,
 }
 
 /// All storage types.
@@ -31,6 +40,10 @@ pub enum Storage {
     Physical(PhysicalDrivePartition),
     SubDirectory(Directory),
     Online(OnlineStorage),
+    Network(NetworkShare),
+    Rclone(RcloneRemote),
+    Archive(Archive),
+    Offline(OfflineMedia),
 }
 
 impl Storage {
@@ -40,6 +53,10 @@ impl Storage {
             Self::Physical(_) => "PhysicalStorage",
             Self::SubDirectory(_) => "SubDirectory",
             Self::Online(_) => "OnlineStorage",
+            Self::Network(_) => "NetworkShare",
+            Self::Rclone(_) => "RcloneRemote",
+            Self::Archive(_) => "Archive",
+            Self::Offline(_) => "OfflineMedia",
         }
     }
 
@@ -49,6 +66,10 @@ impl Storage {
             Self::Physical(_) => "P",
             Self::SubDirectory(_) => "S",
             Self::Online(_) => "O",
+            Self::Network(_) => "N",
+            Self::Rclone(_) => "R",
+            Self::Archive(_) => "A",
+            Self::Offline(_) => "M",
         }
     }
 
@@ -57,6 +78,20 @@ impl Storage {
             Storage::Physical(_) => Style::new().cyan(),
             Storage::SubDirectory(_) => Style::new().yellow(),
             Storage::Online(_) => Style::new().green(),
+            Storage::Network(_) => Style::new().blue(),
+            Storage::Rclone(_) => Style::new().magenta(),
+            Storage::Archive(_) => Style::new().yellow().italic(),
+            Storage::Offline(_) => Style::new().blue().bright(),
+        }
+    }
+
+    /// Whether the storage is mounted on `device`.
+    /// `None` if it can't be told or the storage is not bound to `device`.
+    /// Only network shares are checked for now.
+    pub fn is_mounted(&self, device: &devices::Device) -> Option<bool> {
+        match self {
+            Storage::Network(s) => s.is_mounted(device),
+            _ => None,
         }
     }
 }
@@ -67,6 +102,10 @@ impl StorageExt for Storage {
             Self::Physical(s) => s.name(),
             Self::SubDirectory(s) => s.name(),
             Self::Online(s) => s.name(),
+            Self::Network(s) => s.name(),
+            Self::Rclone(s) => s.name(),
+            Self::Archive(s) => s.name(),
+            Self::Offline(s) => s.name(),
         }
     }
 
@@ -75,6 +114,10 @@ impl StorageExt for Storage {
             Self::Physical(s) => s.local_info(device),
             Self::SubDirectory(s) => s.local_info(device),
             Self::Online(s) => s.local_info(device),
+            Self::Network(s) => s.local_info(device),
+            Self::Rclone(s) => s.local_info(device),
+            Self::Archive(s) => s.local_info(device),
+            Self::Offline(s) => s.local_info(device),
         }
     }
 
@@ -83,6 +126,10 @@ impl StorageExt for Storage {
             Self::Physical(s) => s.mount_path(device),
             Self::SubDirectory(s) => s.mount_path(device),
             Self::Online(s) => s.mount_path(device),
+            Self::Network(s) => s.mount_path(device),
+            Self::Rclone(s) => s.mount_path(device),
+            Self::Archive(s) => s.mount_path(device),
+            Self::Offline(s) => s.mount_path(device),
         }
     }
 
@@ -96,6 +143,34 @@ impl StorageExt for Storage {
             Storage::Physical(s) => s.bound_on_device(alias, mount_point, device),
             Storage::SubDirectory(s) => s.bound_on_device(alias, mount_point, device),
             Storage::Online(s) => s.bound_on_device(alias, mount_point, device),
+            Storage::Network(s) => s.bound_on_device(alias, mount_point, device),
+            Storage::Rclone(s) => s.bound_on_device(alias, mount_point, device),
+            Storage::Archive(s) => s.bound_on_device(alias, mount_point, device),
+            Storage::Offline(s) => s.bound_on_device(alias, mount_point, device),
+        }
+    }
+
+    fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        match self {
+            Storage::Physical(s) => s.rename_device(old_name, new_name),
+            Storage::SubDirectory(s) => s.rename_device(old_name, new_name),
+            Storage::Online(s) => s.rename_device(old_name, new_name),
+            Storage::Network(s) => s.rename_device(old_name, new_name),
+            Storage::Rclone(s) => s.rename_device(old_name, new_name),
+            Storage::Archive(s) => s.rename_device(old_name, new_name),
+            Storage::Offline(s) => s.rename_device(old_name, new_name),
+        }
+    }
+
+    fn unbind_device(&mut self, device_name: &str) -> Option<local_info::LocalInfo> {
+        match self {
+            Storage::Physical(s) => s.unbind_device(device_name),
+            Storage::SubDirectory(s) => s.unbind_device(device_name),
+            Storage::Online(s) => s.unbind_device(device_name),
+            Storage::Network(s) => s.unbind_device(device_name),
+            Storage::Rclone(s) => s.unbind_device(device_name),
+            Storage::Archive(s) => s.unbind_device(device_name),
+            Storage::Offline(s) => s.unbind_device(device_name),
         }
     }
 
@@ -104,6 +179,10 @@ impl StorageExt for Storage {
             Storage::Physical(s) => s.capacity(),
             Storage::SubDirectory(s) => s.capacity(),
             Storage::Online(s) => s.capacity(),
+            Storage::Network(s) => s.capacity(),
+            Storage::Rclone(s) => s.capacity(),
+            Storage::Archive(s) => s.capacity(),
+            Storage::Offline(s) => s.capacity(),
         }
     }
 
@@ -112,6 +191,34 @@ impl StorageExt for Storage {
             Storage::Physical(s) => s.parent(storages),
             Storage::SubDirectory(s) => s.parent(storages),
             Storage::Online(s) => s.parent(storages),
+            Storage::Network(s) => s.parent(storages),
+            Storage::Rclone(s) => s.parent(storages),
+            Storage::Archive(s) => s.parent(storages),
+            Storage::Offline(s) => s.parent(storages),
+        }
+    }
+
+    fn usage(&self) -> Option<&usage::UsageHistory> {
+        match self {
+            Storage::Physical(s) => s.usage(),
+            Storage::SubDirectory(s) => s.usage(),
+            Storage::Online(s) => s.usage(),
+            Storage::Network(s) => s.usage(),
+            Storage::Rclone(s) => s.usage(),
+            Storage::Archive(s) => s.usage(),
+            Storage::Offline(s) => s.usage(),
+        }
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut usage::UsageHistory> {
+        match self {
+            Storage::Physical(s) => s.usage_mut(),
+            Storage::SubDirectory(s) => s.usage_mut(),
+            Storage::Online(s) => s.usage_mut(),
+            Storage::Network(s) => s.usage_mut(),
+            Storage::Rclone(s) => s.usage_mut(),
+            Storage::Archive(s) => s.usage_mut(),
+            Storage::Offline(s) => s.usage_mut(),
         }
     }
 }
@@ -122,6 +229,10 @@ impl fmt::Display for Storage {
             Self::Physical(s) => s.fmt(f),
             Self::SubDirectory(s) => s.fmt(f),
             Self::Online(s) => s.fmt(f),
+            Self::Network(s) => s.fmt(f),
+            Self::Rclone(s) => s.fmt(f),
+            Self::Archive(s) => s.fmt(f),
+            Self::Offline(s) => s.fmt(f),
         }
     }
 }
@@ -155,14 +266,34 @@ pub trait StorageExt {
         device: &devices::Device,
     ) -> Result<()>;
 
+    /// Move local info of device `old_name` to `new_name`.
+    fn rename_device(&mut self, old_name: &str, new_name: &str);
+
+    /// Remove local info of device `device_name`.
+    /// Returns the removed one if the storage was bound on the device.
+    fn unbind_device(&mut self, device_name: &str) -> Option<local_info::LocalInfo>;
+
     /// Get parent
     fn parent<'a>(&'a self, storages: &'a Storages) -> Option<&'a Storage>;
+
+    /// History of used and free bytes.
+    /// Since [Directory] shares the usage with its parent, it has `None`.
+    fn usage(&self) -> Option<&usage::UsageHistory>;
+
+    /// Mutable reference to [`StorageExt::usage`] to record new usage.
+    fn usage_mut(&mut self) -> Option<&mut usage::UsageHistory>;
 }
 
+pub mod archive;
 pub mod directory;
 pub mod local_info;
+pub mod location;
+pub mod network_share;
+pub mod offline_media;
 pub mod online_storage;
 pub mod physical_drive_partition;
+pub mod rclone_remote;
+pub mod usage;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Storages {
@@ -182,6 +313,62 @@ impl Storages {
         self.list.get(name)
     }
 
+    /// Get the root [`Storage`] of `storage` by following [`StorageExt::parent`].
+    /// Returns [`None`] if some parent doesn't exist.
+    pub fn root<'a>(&'a self, storage: &'a Storage) -> Option<&'a Storage> {
+        let mut current = storage;
+        // guard against circular dependency
+        for _ in 0..=self.list.len() {
+            match current {
+                Storage::SubDirectory(s) => current = s.parent(self)?,
+                Storage::Archive(s) => current = s.parent(self)?,
+                _ => return Some(current),
+            }
+        }
+        error!("Circular dependency found on storage {}", storage.name());
+        None
+    }
+
+    /// Physical drive partition which `storage` is on, following its parents.
+    pub fn partition<'a>(
+        &'a self,
+        storage: &'a Storage,
+    ) -> Option<&'a physical_drive_partition::PhysicalDrivePartition> {
+        match self.root(storage)? {
+            Storage::Physical(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Current location of `storage`, which is the one of its root physical drive or offline media.
+    pub fn location<'a>(&'a self, storage: &'a Storage) -> Option<&'a location::LocationRecord> {
+        match self.root(storage)? {
+            Storage::Physical(s) => s.location(),
+            Storage::Offline(s) => s.location(),
+            _ => None,
+        }
+    }
+
+    /// Storage names in depth-first order with their depth, each followed by its children.
+    pub fn tree(&self) -> Vec<(usize, String)> {
+        fn push_children(
+            storages: &Storages,
+            parent: Option<&String>,
+            depth: usize,
+            rows: &mut Vec<(usize, String)>,
+        ) {
+            for storage in storages.list.values() {
+                if storage.parent(storages).map(|p| p.name()) == parent {
+                    rows.push((depth, storage.name().clone()));
+                    push_children(storages, Some(storage.name()), depth + 1, rows);
+                }
+            }
+        }
+        let mut rows = vec![];
+        push_children(self, None, 0, &mut rows);
+        rows
+    }
+
     /// Add new [`Storage`] to [`Storages`]
     /// New `storage` must has new unique name.
     pub fn add(&mut self, storage: Storage) -> Result<()> {
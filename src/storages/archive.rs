@@ -0,0 +1,249 @@
+//! Archive files and backup repositories inside other storages.
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::{fmt, path};
+
+use crate::devices;
+use crate::util;
+
+use super::{local_info::LocalInfo, usage::UsageHistory, Storage, StorageExt, Storages};
+
+/// Format of the archive or repository.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    Tar,
+    Zip,
+    #[value(name = "7z")]
+    #[serde(rename = "7z")]
+    SevenZip,
+    Veracrypt,
+    Restic,
+    Borg,
+    Kopia,
+    Other,
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::SevenZip => "7z",
+            ArchiveFormat::Veracrypt => "veracrypt",
+            ArchiveFormat::Restic => "restic",
+            ArchiveFormat::Borg => "borg",
+            ArchiveFormat::Kopia => "kopia",
+            ArchiveFormat::Other => "other",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Archive file, encrypted container or backup repository in other [`Storage`].
+/// Its content is not browsable as plain files, so it has no mount path by itself.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Archive {
+    /// ID.
+    name: String,
+    /// ID of parent storage.
+    parent: String,
+    /// Relative path to the parent storage.
+    relative_path: Vec<String>,
+    pub format: ArchiveFormat,
+    pub encrypted: bool,
+    /// ID of the repository, like restic's repository ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository_id: Option<String>,
+    pub notes: String,
+}
+
+impl Archive {
+    /// - `name`: id
+    /// - `parent`: where the archive locates.
+    /// - `relative_path`: path from root of the parent storage.
+    fn new(
+        name: String,
+        parent: String,
+        relative_path: path::PathBuf,
+        format: ArchiveFormat,
+        encrypted: bool,
+        repository_id: Option<String>,
+        notes: String,
+    ) -> Result<Archive> {
+        let relative_path = relative_path
+            .components()
+            .map(|c| c.as_os_str().to_str().map(|s| s.to_owned()))
+            .collect::<Option<Vec<_>>>()
+            .context("Path contains non-utf8 character")?;
+        Ok(Archive {
+            name,
+            parent,
+            relative_path,
+            format,
+            encrypted,
+            repository_id,
+            notes,
+        })
+    }
+
+    /// Archive at `path` on `device`. The parent is the nearest storage containing `path`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_from_device_path(
+        name: String,
+        path: path::PathBuf,
+        format: ArchiveFormat,
+        encrypted: bool,
+        repository_id: Option<String>,
+        notes: String,
+        device: &devices::Device,
+        storages: &Storages,
+    ) -> Result<Archive> {
+        let (parent, diff_path) = util::min_parent_storage(&path, storages, device)
+            .context("Failed to find the parent storage of the archive")?;
+        trace!("Selected parent: {}", parent.name());
+        Archive::new(
+            name,
+            parent.name().to_string(),
+            diff_path,
+            format,
+            encrypted,
+            repository_id,
+            notes,
+        )
+    }
+
+    /// Path of the archive file or repository on `device`, resolved from the parent.
+    pub fn path(&self, storages: &Storages, device: &devices::Device) -> Option<path::PathBuf> {
+        let parent_mount_path = self.parent(storages)?.mount_path(device)?;
+        Some(parent_mount_path.join(self.relative_path.iter().collect::<PathBuf>()))
+    }
+
+    /// Short description like `restic, encrypted`.
+    pub fn description(&self) -> String {
+        let mut description = self.format.to_string();
+        if self.encrypted {
+            description.push_str(", encrypted");
+        }
+        if let Some(id) = &self.repository_id {
+            description.push_str(&format!(", id {}", id));
+        }
+        description
+    }
+}
+
+impl StorageExt for Archive {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn capacity(&self) -> Option<u64> {
+        None
+    }
+
+    fn local_info(&self, _device: &devices::Device) -> Option<&LocalInfo> {
+        None
+    }
+
+    /// Always `None` since the content is not browsable. See [`Archive::path`].
+    fn mount_path(&self, _device: &devices::Device) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    fn bound_on_device(
+        &mut self,
+        _alias: String,
+        _mount_point: path::PathBuf,
+        _device: &devices::Device,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "Archive {} follows its parent {}. Bind the parent instead.",
+            self.name,
+            self.parent
+        ))
+    }
+
+    fn rename_device(&mut self, _old_name: &str, _new_name: &str) {}
+
+    fn unbind_device(&mut self, _device_name: &str) -> Option<LocalInfo> {
+        None
+    }
+
+    fn parent<'a>(&'a self, storages: &'a Storages) -> Option<&'a Storage> {
+        storages.get(&self.parent)
+    }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        None
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        None
+    }
+}
+
+impl fmt::Display for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "A {name:<10} < {parent:<10}{relative_path:<10} : {description}",
+            name = self.name(),
+            parent = self.parent,
+            relative_path = self.relative_path.iter().collect::<PathBuf>().display(),
+            description = self.description(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{
+        devices::Device,
+        storages::{online_storage::OnlineStorage, Storage, StorageExt, Storages},
+    };
+
+    use super::{Archive, ArchiveFormat};
+
+    #[test]
+    fn archive_path() {
+        let device = Device::new("dev".to_string());
+        let mut storages = Storages::new();
+        storages
+            .add(Storage::Online(OnlineStorage::new(
+                "online".to_string(),
+                "provider".to_string(),
+                1_000_000,
+                "alias".to_string(),
+                PathBuf::from("/mnt/online"),
+                &device,
+            )))
+            .unwrap();
+        let archive = Archive::try_from_device_path(
+            "repo".to_string(),
+            PathBuf::from("/mnt/online/restic"),
+            ArchiveFormat::Restic,
+            true,
+            Some("abc123".to_string()),
+            "".to_string(),
+            &device,
+            &storages,
+        )
+        .unwrap();
+        assert_eq!(archive.parent(&storages).unwrap().name(), "online");
+        assert_eq!(
+            archive.path(&storages, &device),
+            Some(PathBuf::from("/mnt/online/restic"))
+        );
+        assert_eq!(archive.mount_path(&device), None);
+        assert_eq!(archive.description(), "restic, encrypted, id abc123");
+        assert_eq!(
+            archive.path(&storages, &Device::new("other".to_string())),
+            None
+        );
+    }
+}
@@ -8,7 +8,7 @@ use std::{collections::BTreeMap, fmt, path};
 use crate::devices;
 use crate::util;
 
-use super::{local_info::LocalInfo, Storage, StorageExt, Storages};
+use super::{local_info::LocalInfo, usage::UsageHistory, Storage, StorageExt, Storages};
 
 /// Subdirectory of other [Storage]s.
 #[derive(Serialize, Deserialize, Debug)]
@@ -126,10 +126,28 @@ impl StorageExt for Directory {
         Ok(())
     }
 
+    fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        if let Some(info) = self.local_infos.remove(old_name) {
+            self.local_infos.insert(new_name.to_string(), info);
+        }
+    }
+
+    fn unbind_device(&mut self, device_name: &str) -> Option<LocalInfo> {
+        self.local_infos.remove(device_name)
+    }
+
     // Get parent `&Storage` of directory.
     fn parent<'a>(&'a self, storages: &'a Storages) -> Option<&'a Storage> {
         storages.get(&self.parent)
     }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        None
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        None
+    }
 }
 
 impl fmt::Display for Directory {
@@ -0,0 +1,74 @@
+//! Physical location history of drives and offline media.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Drive or media was moved to `location` at `datetime`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LocationRecord {
+    pub datetime: DateTime<Local>,
+    /// Where the drive is kept, like "home safe" or "parents' house".
+    pub location: String,
+    /// Whether the location is away from the site of the original data.
+    #[serde(default)]
+    pub offsite: bool,
+}
+
+impl LocationRecord {
+    pub fn new_with_current_time(location: String, offsite: bool) -> LocationRecord {
+        LocationRecord {
+            datetime: Local::now(),
+            location,
+            offsite,
+        }
+    }
+
+    /// `on-site` or `off-site`.
+    pub fn site(&self) -> &'static str {
+        if self.offsite { "off-site" } else { "on-site" }
+    }
+}
+
+/// All [`LocationRecord`]s of a drive or media, ordered from the oldest.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(transparent)]
+pub struct LocationHistory {
+    records: Vec<LocationRecord>,
+}
+
+impl LocationHistory {
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Add new `record`. Unlike usage, the whole history is kept.
+    pub fn push(&mut self, record: LocationRecord) {
+        self.records.push(record);
+        self.records.sort_by_key(|r| r.datetime);
+    }
+
+    /// Current location.
+    pub fn current(&self) -> Option<&LocationRecord> {
+        self.records.last()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeDelta;
+
+    use super::{LocationHistory, LocationRecord};
+
+    #[test]
+    fn location_history() {
+        let mut history = LocationHistory::default();
+        assert!(history.current().is_none());
+        let home = LocationRecord::new_with_current_time("home".to_string(), false);
+        let mut parents = LocationRecord::new_with_current_time("parents".to_string(), true);
+        parents.datetime = home.datetime - TimeDelta::days(1);
+        history.push(home.clone());
+        history.push(parents);
+        assert_eq!(history.current(), Some(&home));
+        assert_eq!(history.current().unwrap().site(), "on-site");
+    }
+}
@@ -0,0 +1,251 @@
+//! Network share (SMB, NFS, ...) mounted on devices.
+
+use anyhow::Result;
+use byte_unit::Byte;
+use byte_unit::UnitType;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path;
+
+use crate::devices;
+
+use super::{
+    local_info::{self, LocalInfo},
+    usage::UsageHistory,
+    Storage, StorageExt, Storages,
+};
+
+/// Protocol to access the share.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkProtocol {
+    Smb,
+    Nfs,
+    Sshfs,
+    Webdav,
+}
+
+impl fmt::Display for NetworkProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            NetworkProtocol::Smb => "smb",
+            NetworkProtocol::Nfs => "nfs",
+            NetworkProtocol::Sshfs => "sshfs",
+            NetworkProtocol::Webdav => "webdav",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Share or export on a file server, like a NAS.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NetworkShare {
+    /// ID.
+    name: String,
+    /// Hostname or address of the server.
+    pub server: String,
+    /// Share name or export path on the server.
+    pub share: String,
+    pub protocol: NetworkProtocol,
+    /// Capacity in bytes, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capacity: Option<u64>,
+    /// Device and local info pairs. Mount points differ among devices.
+    local_infos: BTreeMap<String, LocalInfo>,
+    /// History of used and free bytes.
+    #[serde(default, skip_serializing_if = "UsageHistory::is_empty")]
+    usage: UsageHistory,
+}
+
+impl NetworkShare {
+    /// # Arguments
+    /// - alias: for [`LocalInfo`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: String,
+        server: String,
+        share: String,
+        protocol: NetworkProtocol,
+        capacity: Option<u64>,
+        alias: String,
+        path: path::PathBuf,
+        device: &devices::Device,
+    ) -> NetworkShare {
+        let local_info = local_info::LocalInfo::new(alias, path);
+        NetworkShare {
+            name,
+            server,
+            share,
+            protocol,
+            capacity,
+            local_infos: BTreeMap::from([(device.name(), local_info)]),
+            usage: UsageHistory::default(),
+        }
+    }
+
+    /// Location of the share like `smb://server/share`.
+    pub fn url(&self) -> String {
+        format!(
+            "{}://{}/{}",
+            self.protocol,
+            self.server,
+            self.share.trim_start_matches('/')
+        )
+    }
+
+    /// Whether the share is mounted on `device`.
+    /// `None` if the share is not bound to `device`.
+    pub fn is_mounted(&self, device: &devices::Device) -> Option<bool> {
+        self.mount_path(device).map(|path| is_mount_point(&path))
+    }
+}
+
+/// Whether `path` is a mount point of a filesystem.
+#[cfg(target_os = "linux")]
+pub fn is_mount_point(path: &path::Path) -> bool {
+    let Ok(mountinfo) = std::fs::read_to_string("/proc/self/mountinfo") else {
+        return path.exists();
+    };
+    mountinfo
+        .lines()
+        .filter_map(|line| line.split(' ').nth(4))
+        .any(|mount_point| path::Path::new(&unescape_mountinfo(mount_point)) == path)
+}
+
+/// Whether `path` is a mount point of a filesystem.
+/// Only checks the existence on this platform.
+#[cfg(not(target_os = "linux"))]
+pub fn is_mount_point(path: &path::Path) -> bool {
+    path.exists()
+}
+
+/// Decode octal escapes like `\040` in `/proc/self/mountinfo`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn unescape_mountinfo(s: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'\\'
+            && tail.len() >= 3
+            && let Ok(code) = u8::from_str_radix(&String::from_utf8_lossy(&tail[..3]), 8)
+        {
+            bytes.push(code);
+            rest = &tail[3..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+impl StorageExt for NetworkShare {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn capacity(&self) -> Option<u64> {
+        self.capacity
+    }
+
+    fn local_info(&self, device: &devices::Device) -> Option<&LocalInfo> {
+        self.local_infos.get(&device.name())
+    }
+
+    fn mount_path(&self, device: &devices::Device) -> Option<std::path::PathBuf> {
+        self.local_infos
+            .get(&device.name())
+            .map(|info| info.mount_path())
+    }
+
+    fn bound_on_device(
+        &mut self,
+        alias: String,
+        mount_point: path::PathBuf,
+        device: &devices::Device,
+    ) -> Result<()> {
+        match self
+            .local_infos
+            .insert(device.name(), LocalInfo::new(alias, mount_point))
+        {
+            Some(old) => info!("Value replaced. Old value: {:?}", old),
+            None => info!("New value inserted."),
+        };
+        Ok(())
+    }
+
+    fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        if let Some(info) = self.local_infos.remove(old_name) {
+            self.local_infos.insert(new_name.to_string(), info);
+        }
+    }
+
+    fn unbind_device(&mut self, device_name: &str) -> Option<LocalInfo> {
+        self.local_infos.remove(device_name)
+    }
+
+    fn parent(&self, _storages: &Storages) -> Option<&Storage> {
+        None
+    }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        Some(&self.usage)
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        Some(&mut self.usage)
+    }
+}
+
+impl fmt::Display for NetworkShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = match self.capacity {
+            Some(capacity) => format!(
+                "{:.2}",
+                Byte::from_u64(capacity).get_appropriate_unit(UnitType::Binary)
+            ),
+            None => "".to_string(),
+        };
+        write!(
+            f,
+            "N {name:<10} {size:<10}    {url}",
+            name = self.name(),
+            url = self.url(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::devices::Device;
+
+    use super::{is_mount_point, unescape_mountinfo, NetworkProtocol, NetworkShare};
+
+    #[test]
+    fn network_share() {
+        let device = Device::new("dev".to_string());
+        let share = NetworkShare::new(
+            "nas".to_string(),
+            "nas.local".to_string(),
+            "/export/backup".to_string(),
+            NetworkProtocol::Nfs,
+            None,
+            "nas".to_string(),
+            PathBuf::from("/nonexistent/xdbm/nas"),
+            &device,
+        );
+        assert_eq!(share.url(), "nfs://nas.local/export/backup");
+        assert_eq!(share.is_mounted(&device), Some(false));
+        assert_eq!(share.is_mounted(&Device::new("other".to_string())), None);
+    }
+
+    #[test]
+    fn mount_point() {
+        assert_eq!(unescape_mountinfo("/mnt/my\\040share"), "/mnt/my share");
+        assert!(!is_mount_point(&PathBuf::from("/nonexistent/xdbm")));
+    }
+}
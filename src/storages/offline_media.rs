@@ -0,0 +1,205 @@
+//! Offline media like optical discs and tapes, which are not mounted on any device.
+
+use anyhow::{anyhow, Result};
+use byte_unit::Byte;
+use byte_unit::UnitType;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path;
+
+use crate::devices;
+
+use super::{
+    local_info::LocalInfo,
+    location::{LocationHistory, LocationRecord},
+    usage::UsageHistory,
+    Storage, StorageExt, Storages,
+};
+
+/// Kind of the offline media.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Cd,
+    Dvd,
+    Bluray,
+    Tape,
+    Other,
+}
+
+impl fmt::Display for MediaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            MediaKind::Cd => "CD",
+            MediaKind::Dvd => "DVD",
+            MediaKind::Bluray => "Blu-ray",
+            MediaKind::Tape => "tape",
+            MediaKind::Other => "media",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Media written once and kept somewhere, like Blu-ray discs and LTO tapes.
+/// It is identified by the physical label instead of mount points.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OfflineMedia {
+    /// ID.
+    name: String,
+    pub kind: MediaKind,
+    /// Label written on the media.
+    pub label: String,
+    /// Where the media has been kept.
+    location: LocationHistory,
+    /// Date when the media was written.
+    pub written: NaiveDate,
+    /// Capacity in bytes, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capacity: Option<u64>,
+}
+
+impl OfflineMedia {
+    pub fn new(
+        name: String,
+        kind: MediaKind,
+        label: String,
+        location: String,
+        written: NaiveDate,
+        capacity: Option<u64>,
+    ) -> OfflineMedia {
+        let mut history = LocationHistory::default();
+        history.push(LocationRecord::new_with_current_time(location, false));
+        OfflineMedia {
+            name,
+            kind,
+            label,
+            location: history,
+            written,
+            capacity,
+        }
+    }
+
+    /// Current location of the media.
+    pub fn location(&self) -> Option<&LocationRecord> {
+        self.location.current()
+    }
+
+    /// Record that the media is moved to `location` now.
+    pub fn move_to(&mut self, location: String, offsite: bool) {
+        self.location
+            .push(LocationRecord::new_with_current_time(location, offsite));
+    }
+
+    /// Location of `relative_path` in the media, like `BD-001:/photos`.
+    pub fn media_path(&self, relative_path: &[String]) -> String {
+        format!("{}:/{}", self.label, relative_path.join("/"))
+    }
+
+    /// Short description like `Blu-ray at home safe (on-site), written 2026-01-03`.
+    pub fn description(&self) -> String {
+        let location = match self.location() {
+            Some(location) => format!("{} ({})", location.location, location.site()),
+            None => "unknown location".to_string(),
+        };
+        format!(
+            "{} at {}, written {}",
+            self.kind,
+            location,
+            self.written.format("%Y-%m-%d")
+        )
+    }
+}
+
+impl StorageExt for OfflineMedia {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn capacity(&self) -> Option<u64> {
+        self.capacity
+    }
+
+    fn local_info(&self, _device: &devices::Device) -> Option<&LocalInfo> {
+        None
+    }
+
+    fn mount_path(&self, _device: &devices::Device) -> Option<path::PathBuf> {
+        None
+    }
+
+    fn bound_on_device(
+        &mut self,
+        _alias: String,
+        _mount_point: path::PathBuf,
+        _device: &devices::Device,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "Offline media {} is not bound on devices.",
+            self.name
+        ))
+    }
+
+    fn rename_device(&mut self, _old_name: &str, _new_name: &str) {}
+
+    fn unbind_device(&mut self, _device_name: &str) -> Option<LocalInfo> {
+        None
+    }
+
+    fn parent(&self, _storages: &Storages) -> Option<&Storage> {
+        None
+    }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        None
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        None
+    }
+}
+
+impl fmt::Display for OfflineMedia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = match self.capacity {
+            Some(capacity) => format!(
+                "{:.2}",
+                Byte::from_u64(capacity).get_appropriate_unit(UnitType::Binary)
+            ),
+            None => "".to_string(),
+        };
+        write!(
+            f,
+            "M {name:<10} {size:<10}    {label} ({description})",
+            name = self.name(),
+            label = self.label,
+            description = self.description(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::{MediaKind, OfflineMedia};
+
+    #[test]
+    fn offline_media() {
+        let media = OfflineMedia::new(
+            "disc".to_string(),
+            MediaKind::Bluray,
+            "BD-001".to_string(),
+            "home safe".to_string(),
+            NaiveDate::from_ymd_opt(2026, 1, 3).unwrap(),
+            None,
+        );
+        assert_eq!(media.media_path(&["photos".to_string()]), "BD-001:/photos");
+        assert_eq!(media.media_path(&[]), "BD-001:/");
+        assert_eq!(
+            media.description(),
+            "Blu-ray at home safe (on-site), written 2026-01-03"
+        );
+    }
+}
@@ -12,6 +12,7 @@ use crate::devices;
 
 use super::{
     local_info::{self, LocalInfo},
+    usage::UsageHistory,
     Storage, StorageExt, Storages,
 };
 
@@ -25,6 +26,9 @@ pub struct OnlineStorage {
     capacity: u64,
     /// Device and local info pairs.
     local_infos: BTreeMap<String, LocalInfo>,
+    /// History of used and free bytes.
+    #[serde(default, skip_serializing_if = "UsageHistory::is_empty")]
+    usage: UsageHistory,
 }
 
 impl OnlineStorage {
@@ -44,6 +48,7 @@ impl OnlineStorage {
             provider,
             capacity,
             local_infos: BTreeMap::from([(device.name(), local_info)]),
+            usage: UsageHistory::default(),
         }
     }
 }
@@ -83,9 +88,27 @@ impl StorageExt for OnlineStorage {
         Ok(())
     }
 
+    fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        if let Some(info) = self.local_infos.remove(old_name) {
+            self.local_infos.insert(new_name.to_string(), info);
+        }
+    }
+
+    fn unbind_device(&mut self, device_name: &str) -> Option<LocalInfo> {
+        self.local_infos.remove(device_name)
+    }
+
     fn parent(&self, _storages: &Storages) -> Option<&Storage> {
         None
     }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        Some(&self.usage)
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        Some(&mut self.usage)
+    }
 }
 
 impl fmt::Display for OnlineStorage {
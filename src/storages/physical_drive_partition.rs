@@ -5,12 +5,60 @@ use crate::devices::Device;
 use crate::storages::{Storage, StorageExt, Storages};
 use anyhow::{anyhow, Context, Result};
 use byte_unit::{Byte, UnitType};
+use chrono::NaiveDate;
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::path::{self, Path};
-use std::{collections::BTreeMap, fmt};
+use std::path::{self, Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    fmt, fs,
+};
 use sysinfo::{Disk, Disks};
 
 use super::local_info::{self, LocalInfo};
+use super::location::{LocationHistory, LocationRecord};
+use super::usage::UsageHistory;
+
+/// Lifecycle status of the drive.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DriveStatus {
+    #[default]
+    Active,
+    /// No longer used for backups, like rotated out.
+    Retired,
+    /// Broken and shouldn't be trusted.
+    Failed,
+}
+
+impl DriveStatus {
+    pub fn is_active(&self) -> bool {
+        *self == DriveStatus::Active
+    }
+}
+
+impl fmt::Display for DriveStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DriveStatus::Active => "active",
+            DriveStatus::Retired => "retired",
+            DriveStatus::Failed => "failed",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Lifecycle info of the drive to update. `None` fields are left unchanged.
+#[derive(Debug, Default)]
+pub struct DriveInfo {
+    pub serial: Option<String>,
+    pub model: Option<String>,
+    pub purchased: Option<NaiveDate>,
+    pub warranty_end: Option<NaiveDate>,
+    pub status: Option<DriveStatus>,
+    pub drive: Option<String>,
+}
 
 /// Partitoin of physical (on-premises) drive.
 #[derive(Serialize, Deserialize, Debug)]
@@ -20,9 +68,34 @@ pub struct PhysicalDrivePartition {
     capacity: u64,
     fs: String,
     is_removable: bool,
+    /// Filesystem UUID of the partition, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<String>,
     // system_names: BTreeMap<String, String>,
     /// [`Device`] name and [`LocalInfo`] mapping.
     local_infos: BTreeMap<String, LocalInfo>,
+    /// History of used and free bytes.
+    #[serde(default, skip_serializing_if = "UsageHistory::is_empty")]
+    usage: UsageHistory,
+    /// Where the drive has been kept.
+    #[serde(default, skip_serializing_if = "LocationHistory::is_empty")]
+    location: LocationHistory,
+    /// Serial number of the drive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    /// Model name of the drive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchased: Option<NaiveDate>,
+    /// Last day of the warranty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warranty_end: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "DriveStatus::is_active")]
+    pub status: DriveStatus,
+    /// Name of the [`crate::drives::Drive`] which the partition is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drive: Option<String>,
 }
 
 impl PhysicalDrivePartition {
@@ -41,7 +114,16 @@ impl PhysicalDrivePartition {
             capacity,
             fs,
             is_removable,
+            uuid: None,
             local_infos: BTreeMap::from([(device.name(), local_info)]),
+            usage: UsageHistory::default(),
+            location: LocationHistory::default(),
+            serial: None,
+            model: None,
+            purchased: None,
+            warranty_end: None,
+            status: DriveStatus::default(),
+            drive: None,
         }
     }
 
@@ -50,27 +132,44 @@ impl PhysicalDrivePartition {
         disk: &sysinfo::Disk,
         name: String,
         device: Device,
+    ) -> Result<PhysicalDrivePartition> {
+        Self::try_from_mounted_disk(&MountedDisk::from(disk), name, device)
+    }
+
+    fn try_from_mounted_disk(
+        disk: &MountedDisk,
+        name: String,
+        device: Device,
     ) -> Result<PhysicalDrivePartition> {
         let alias = disk
-            .name()
+            .name
             .to_str()
             .context("Failed to convert storage name to valid str.")?
             .to_string();
-        let fs = disk.file_system();
+        let fs = &disk.file_system;
         trace!("fs: {:?}", fs);
         let fs: String = fs
             .to_str()
             .context("Failed to convert file_system osstr")?
             .to_owned();
-        let local_info = LocalInfo::new(alias, disk.mount_point().to_path_buf());
+        let local_info = LocalInfo::new(alias, disk.mount_point.clone());
         Ok(PhysicalDrivePartition {
             name,
-            kind: format!("{:?}", disk.kind()),
-            capacity: disk.total_space(),
+            kind: disk.kind.clone(),
+            capacity: disk.total_space,
             fs: fs.to_string(),
-            is_removable: disk.is_removable(),
+            is_removable: disk.is_removable,
+            uuid: partition_uuid(&disk.name),
             // system_names: BTreeMap::from([(device.name(), alias)]),
             local_infos: BTreeMap::from([(device.name(), local_info)]),
+            usage: UsageHistory::default(),
+            location: LocationHistory::default(),
+            serial: None,
+            model: None,
+            purchased: None,
+            warranty_end: None,
+            status: DriveStatus::default(),
+            drive: None,
         })
     }
 
@@ -98,6 +197,103 @@ impl PhysicalDrivePartition {
     pub fn kind(&self) -> &String {
         &self.kind
     }
+
+    pub fn uuid(&self) -> Option<&String> {
+        self.uuid.as_ref()
+    }
+
+    /// Current location of the drive, if recorded.
+    pub fn location(&self) -> Option<&LocationRecord> {
+        self.location.current()
+    }
+
+    /// Record that the drive is moved to `location` now.
+    pub fn move_to(&mut self, location: String, offsite: bool) {
+        self.location
+            .push(LocationRecord::new_with_current_time(location, offsite));
+    }
+
+    /// Overwrite lifecycle info by the given fields of `info`.
+    pub fn update_info(&mut self, info: DriveInfo) {
+        if info.serial.is_some() {
+            self.serial = info.serial;
+        }
+        if info.model.is_some() {
+            self.model = info.model;
+        }
+        if info.purchased.is_some() {
+            self.purchased = info.purchased;
+        }
+        if info.warranty_end.is_some() {
+            self.warranty_end = info.warranty_end;
+        }
+        if let Some(status) = info.status {
+            self.status = status;
+        }
+        if info.drive.is_some() {
+            self.drive = info.drive;
+        }
+    }
+
+    /// Lifecycle info like `drive: disk1, model: WD Red, serial: X1, status: retired`.
+    /// `None` if nothing is recorded.
+    pub fn lifecycle_description(&self) -> Option<String> {
+        let mut items = vec![];
+        if let Some(drive) = &self.drive {
+            items.push(format!("drive: {}", drive));
+        }
+        if let Some(model) = &self.model {
+            items.push(format!("model: {}", model));
+        }
+        if let Some(serial) = &self.serial {
+            items.push(format!("serial: {}", serial));
+        }
+        if let Some(purchased) = self.purchased {
+            items.push(format!("purchased: {}", purchased.format("%Y-%m-%d")));
+        }
+        if let Some(warranty_end) = self.warranty_end {
+            items.push(format!(
+                "warranty until: {}",
+                warranty_end.format("%Y-%m-%d")
+            ));
+        }
+        if !self.status.is_active() {
+            items.push(format!("status: {}", self.status));
+        }
+        if items.is_empty() {
+            None
+        } else {
+            Some(items.join(", "))
+        }
+    }
+
+    /// Whether the disk with `disk_name` and `uuid` is this partition.
+    /// Matched by UUID if both are known, otherwise by the alias on `device`.
+    pub fn matches_disk(&self, disk_name: &str, uuid: Option<&str>, device: &Device) -> bool {
+        match (self.uuid().map(String::as_str), uuid) {
+            (Some(own), Some(uuid)) => own == uuid,
+            _ => self
+                .local_info(device)
+                .is_some_and(|info| info.alias() == disk_name),
+        }
+    }
+}
+
+/// Filesystem UUID of the block device `disk_name` (e.g. `/dev/sdb1`),
+/// looked up from `/dev/disk/by-uuid`.
+#[cfg(target_os = "linux")]
+pub fn partition_uuid(disk_name: &OsStr) -> Option<String> {
+    let target = fs::canonicalize(disk_name).ok()?;
+    fs::read_dir("/dev/disk/by-uuid")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .find(|entry| fs::canonicalize(entry.path()).is_ok_and(|path| path == target))
+        .and_then(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn partition_uuid(_disk_name: &OsStr) -> Option<String> {
+    None
 }
 
 impl StorageExt for PhysicalDrivePartition {
@@ -135,9 +331,27 @@ impl StorageExt for PhysicalDrivePartition {
         Ok(())
     }
 
+    fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        if let Some(info) = self.local_infos.remove(old_name) {
+            self.local_infos.insert(new_name.to_string(), info);
+        }
+    }
+
+    fn unbind_device(&mut self, device_name: &str) -> Option<LocalInfo> {
+        self.local_infos.remove(device_name)
+    }
+
     fn parent(&self, _storages: &Storages) -> Option<&Storage> {
         None
     }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        Some(&self.usage)
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        Some(&mut self.usage)
+    }
 }
 
 impl fmt::Display for PhysicalDrivePartition {
@@ -173,6 +387,46 @@ pub fn select_physical_storage(
     Ok(storage)
 }
 
+/// Snapshot of a [`Disk`] in sysinfo.
+#[derive(Debug)]
+pub struct MountedDisk {
+    name: OsString,
+    mount_point: PathBuf,
+    file_system: OsString,
+    kind: String,
+    total_space: u64,
+    is_removable: bool,
+}
+
+impl From<&Disk> for MountedDisk {
+    fn from(disk: &Disk) -> Self {
+        MountedDisk {
+            name: disk.name().to_os_string(),
+            mount_point: disk.mount_point().to_path_buf(),
+            file_system: disk.file_system().to_os_string(),
+            kind: format!("{:?}", disk.kind()),
+            total_space: disk.total_space(),
+            is_removable: disk.is_removable(),
+        }
+    }
+}
+
+/// Get physical storage mounted at `mount_point` from `disks`,
+/// which are usually taken from sysinfo.
+pub fn find_physical_storage(
+    disks: impl IntoIterator<Item = MountedDisk>,
+    mount_point: &Path,
+    disk_name: String,
+    device: Device,
+) -> Result<PhysicalDrivePartition> {
+    let disk = disks
+        .into_iter()
+        .find(|disk| disk.mount_point == mount_point)
+        .context(format!("No drive is mounted at {}", mount_point.display()))?;
+    trace!("found disk: {:?}", disk);
+    PhysicalDrivePartition::try_from_mounted_disk(&disk, disk_name, device)
+}
+
 fn select_sysinfo_disk(disks: &sysinfo::Disks) -> Result<&Disk> {
     let available_disks = disks
         .iter()
@@ -212,9 +466,12 @@ mod test {
         devices::Device,
         storages::{local_info::LocalInfo, StorageExt},
     };
-    use std::path::PathBuf;
+    use chrono::NaiveDate;
+    use std::path::{Path, PathBuf};
 
-    use super::PhysicalDrivePartition;
+    use super::{
+        find_physical_storage, DriveInfo, DriveStatus, MountedDisk, PhysicalDrivePartition,
+    };
 
     #[test]
     fn test_new() {
@@ -231,4 +488,68 @@ mod test {
         assert_eq!(storage.name(), "name");
         assert_eq!(storage.capacity(), Some(100));
     }
+
+    #[test]
+    fn find_mounted_disk() {
+        let device = Device::new("test_device".to_string());
+        let disks = || {
+            ["/", "/mnt/usb"].map(|mount_point| MountedDisk {
+                name: format!("disk at {}", mount_point).into(),
+                mount_point: PathBuf::from(mount_point),
+                file_system: "ext4".into(),
+                kind: "SSD".to_string(),
+                total_space: 1000,
+                is_removable: mount_point != "/",
+            })
+        };
+        let storage = find_physical_storage(
+            disks(),
+            Path::new("/mnt/usb"),
+            "usb".to_string(),
+            device.clone(),
+        )
+        .unwrap();
+        assert_eq!(storage.name(), "usb");
+        assert_eq!(storage.kind(), "SSD");
+        assert!(storage.is_removable());
+        assert_eq!(storage.mount_path(&device), Some(PathBuf::from("/mnt/usb")));
+        assert_eq!(
+            storage.local_info(&device).unwrap().alias(),
+            "disk at /mnt/usb"
+        );
+
+        // only exact mount points
+        let err = find_physical_storage(disks(), Path::new("/mnt"), "usb".to_string(), device)
+            .unwrap_err();
+        assert!(err.to_string().contains("No drive is mounted at /mnt"));
+    }
+
+    #[test]
+    fn lifecycle_description() {
+        let localinfo = LocalInfo::new("alias".to_string(), PathBuf::from("/mnt/sample"));
+        let mut storage = PhysicalDrivePartition::new(
+            "name".to_string(),
+            "HDD".to_string(),
+            100,
+            "ext4".to_string(),
+            true,
+            localinfo,
+            &Device::new("test_device".to_string()),
+        );
+        assert_eq!(storage.lifecycle_description(), None);
+        storage.update_info(DriveInfo {
+            model: Some("WD Red".to_string()),
+            serial: Some("X1".to_string()),
+            purchased: NaiveDate::from_ymd_opt(2024, 4, 1),
+            ..Default::default()
+        });
+        storage.update_info(DriveInfo {
+            status: Some(DriveStatus::Retired),
+            ..Default::default()
+        });
+        assert_eq!(
+            storage.lifecycle_description().unwrap(),
+            "model: WD Red, serial: X1, purchased: 2024-04-01, status: retired"
+        );
+    }
 }
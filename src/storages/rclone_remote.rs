@@ -0,0 +1,212 @@
+//! Cloud storage accessed by rclone.
+
+use anyhow::{anyhow, Context, Result};
+use byte_unit::Byte;
+use byte_unit::UnitType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path;
+use std::process::Command;
+
+use crate::devices;
+
+use super::{
+    local_info::LocalInfo,
+    usage::{UsageHistory, UsageRecord},
+    Storage, StorageExt, Storages,
+};
+
+/// Environment variable to specify the rclone binary.
+pub const RCLONE_ENV: &str = "XDBM_RCLONE";
+
+/// `rclone` command, or the one given by [`RCLONE_ENV`].
+pub fn rclone_command() -> Command {
+    Command::new(std::env::var_os(RCLONE_ENV).unwrap_or_else(|| "rclone".into()))
+}
+
+/// Remote configured in rclone, like `gdrive:` or `b2:bucket/backup`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RcloneRemote {
+    /// ID.
+    name: String,
+    /// Remote with the root path, like `gdrive:backup`.
+    pub remote: String,
+    /// Device and local info pairs, if the remote is mounted by `rclone mount`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    local_infos: BTreeMap<String, LocalInfo>,
+    /// History of used and free bytes.
+    #[serde(default, skip_serializing_if = "UsageHistory::is_empty")]
+    usage: UsageHistory,
+}
+
+impl RcloneRemote {
+    /// `:` is appended to `remote` if it doesn't have one, so that the name works as a remote.
+    pub fn new(name: String, remote: String) -> RcloneRemote {
+        let remote = if remote.contains(':') {
+            remote
+        } else {
+            format!("{}:", remote)
+        };
+        RcloneRemote {
+            name,
+            remote,
+            local_infos: BTreeMap::new(),
+            usage: UsageHistory::default(),
+        }
+    }
+
+    /// Remote path of `relative_path` in the storage.
+    pub fn remote_path(&self, relative_path: &[String]) -> String {
+        let root = self.remote.trim_end_matches('/');
+        let relative = relative_path.join("/");
+        if relative.is_empty() {
+            root.to_string()
+        } else if root.ends_with(':') {
+            format!("{}{}", root, relative)
+        } else {
+            format!("{}/{}", root, relative)
+        }
+    }
+
+    /// Query used and free bytes by `rclone about`.
+    pub fn about(&self) -> Result<UsageRecord> {
+        let output = rclone_command()
+            .args(["about", "--json", &self.remote])
+            .output()
+            .context("Failed to execute rclone")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "rclone about {} failed: {}",
+                self.remote,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        parse_about(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// Output of `rclone about --json`. Fields not supported by the backend are omitted.
+#[derive(Deserialize)]
+struct About {
+    total: Option<u64>,
+    used: Option<u64>,
+    free: Option<u64>,
+}
+
+fn parse_about(json: &str) -> Result<UsageRecord> {
+    let about: About =
+        serde_json::from_str(json).context("Failed to parse the output of rclone about")?;
+    let (used, free) = match (about.total, about.used, about.free) {
+        (_, Some(used), Some(free)) => (used, free),
+        (Some(total), Some(used), None) => (used, total.saturating_sub(used)),
+        (Some(total), None, Some(free)) => (total.saturating_sub(free), free),
+        _ => return Err(anyhow!("The remote doesn't report its usage")),
+    };
+    Ok(UsageRecord::new_with_current_time(used, free))
+}
+
+impl StorageExt for RcloneRemote {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Total bytes of the last [`RcloneRemote::about`] record.
+    fn capacity(&self) -> Option<u64> {
+        self.usage.latest().map(|record| record.total())
+    }
+
+    fn local_info(&self, device: &devices::Device) -> Option<&LocalInfo> {
+        self.local_infos.get(&device.name())
+    }
+
+    fn mount_path(&self, device: &devices::Device) -> Option<path::PathBuf> {
+        self.local_infos
+            .get(&device.name())
+            .map(|info| info.mount_path())
+    }
+
+    fn bound_on_device(
+        &mut self,
+        alias: String,
+        mount_point: path::PathBuf,
+        device: &devices::Device,
+    ) -> Result<()> {
+        match self
+            .local_infos
+            .insert(device.name(), LocalInfo::new(alias, mount_point))
+        {
+            Some(old) => info!("Value replaced. Old value: {:?}", old),
+            None => info!("New value inserted."),
+        };
+        Ok(())
+    }
+
+    fn rename_device(&mut self, old_name: &str, new_name: &str) {
+        if let Some(info) = self.local_infos.remove(old_name) {
+            self.local_infos.insert(new_name.to_string(), info);
+        }
+    }
+
+    fn unbind_device(&mut self, device_name: &str) -> Option<LocalInfo> {
+        self.local_infos.remove(device_name)
+    }
+
+    fn parent(&self, _storages: &Storages) -> Option<&Storage> {
+        None
+    }
+
+    fn usage(&self) -> Option<&UsageHistory> {
+        Some(&self.usage)
+    }
+
+    fn usage_mut(&mut self) -> Option<&mut UsageHistory> {
+        Some(&mut self.usage)
+    }
+}
+
+impl fmt::Display for RcloneRemote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = match self.capacity() {
+            Some(capacity) => format!(
+                "{:.2}",
+                Byte::from_u64(capacity).get_appropriate_unit(UnitType::Binary)
+            ),
+            None => "".to_string(),
+        };
+        write!(
+            f,
+            "R {name:<10} {size:<10}    {remote}",
+            name = self.name(),
+            remote = self.remote,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_about, RcloneRemote};
+
+    #[test]
+    fn remote_path() {
+        let remote = RcloneRemote::new("gdrive".to_string(), "gdrive".to_string());
+        assert_eq!(remote.remote, "gdrive:");
+        assert_eq!(
+            remote.remote_path(&["a".to_string(), "b".to_string()]),
+            "gdrive:a/b"
+        );
+        let remote = RcloneRemote::new("b2".to_string(), "b2:bucket/backup/".to_string());
+        assert_eq!(remote.remote_path(&["a".to_string()]), "b2:bucket/backup/a");
+        assert_eq!(remote.remote_path(&[]), "b2:bucket/backup");
+    }
+
+    #[test]
+    fn about_json() {
+        let record =
+            parse_about(r#"{"total": 1000, "used": 300, "free": 600, "trashed": 100}"#).unwrap();
+        assert_eq!((record.used, record.free), (300, 600));
+        let record = parse_about(r#"{"total": 1000, "used": 300}"#).unwrap();
+        assert_eq!((record.used, record.free), (300, 700));
+        assert!(parse_about(r#"{"objects": 10}"#).is_err());
+    }
+}
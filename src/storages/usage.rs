@@ -0,0 +1,195 @@
+//! Usage (used and free bytes) history of storages.
+
+use std::path::Path;
+
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Disk, Disks};
+
+/// Number of records kept for each storage.
+/// Older records are dropped when new one is pushed.
+pub const USAGE_HISTORY_LEN: usize = 32;
+
+/// Used and free bytes of a storage at `datetime`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub datetime: DateTime<Local>,
+    pub used: u64,
+    pub free: u64,
+}
+
+impl UsageRecord {
+    pub fn new_with_current_time(used: u64, free: u64) -> UsageRecord {
+        UsageRecord {
+            datetime: Local::now(),
+            used,
+            free,
+        }
+    }
+
+    /// Total bytes at the time of the record.
+    pub fn total(&self) -> u64 {
+        self.used + self.free
+    }
+
+    /// Used ratio in percent.
+    pub fn used_percent(&self) -> Option<f64> {
+        if self.total() == 0 {
+            None
+        } else {
+            Some(self.used as f64 / self.total() as f64 * 100.0)
+        }
+    }
+}
+
+/// Short history of [`UsageRecord`]s, ordered from the oldest.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(transparent)]
+pub struct UsageHistory {
+    records: Vec<UsageRecord>,
+}
+
+impl UsageHistory {
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Add new `record` and drop old ones to keep at most [`USAGE_HISTORY_LEN`] records.
+    pub fn push(&mut self, record: UsageRecord) {
+        self.records.push(record);
+        self.records.sort_by_key(|r| r.datetime);
+        if self.records.len() > USAGE_HISTORY_LEN {
+            let n_drop = self.records.len() - USAGE_HISTORY_LEN;
+            self.records.drain(..n_drop);
+        }
+    }
+
+    /// The latest record.
+    pub fn latest(&self) -> Option<&UsageRecord> {
+        self.records.last()
+    }
+
+    /// Predict when the storage gets full by the linear fit of the used bytes.
+    /// Returns [`None`] if there are not enough records or the usage is not increasing.
+    pub fn predict_full(&self) -> Option<DateTime<Local>> {
+        if self.records.len() < 2 {
+            return None;
+        }
+        let origin = self.records.first()?.datetime;
+        let points: Vec<(f64, f64)> = self
+            .records
+            .iter()
+            .map(|r| ((r.datetime - origin).num_seconds() as f64, r.used as f64))
+            .collect();
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let mean_u = points.iter().map(|(_, u)| u).sum::<f64>() / n;
+        let var_t = points
+            .iter()
+            .map(|(t, _)| (t - mean_t).powi(2))
+            .sum::<f64>();
+        if var_t == 0.0 {
+            return None;
+        }
+        let cov = points
+            .iter()
+            .map(|(t, u)| (t - mean_t) * (u - mean_u))
+            .sum::<f64>();
+        let slope = cov / var_t;
+        if slope <= 0.0 {
+            return None;
+        }
+        let intercept = mean_u - slope * mean_t;
+        let total = self.latest()?.total() as f64;
+        let t_full = (total - intercept) / slope;
+        if !t_full.is_finite() {
+            return None;
+        }
+        // far future beyond the range of chrono is regarded as never
+        origin.checked_add_signed(TimeDelta::try_seconds(t_full as i64)?)
+    }
+}
+
+/// Find the disk mounted at `path` from `disks`.
+/// If `exact` is `false`, the disk which contains `path` is returned.
+pub fn find_disk<'a>(disks: &'a Disks, path: &Path, exact: bool) -> Option<&'a Disk> {
+    disks
+        .iter()
+        .filter(|disk| {
+            if exact {
+                disk.mount_point() == path
+            } else {
+                path.starts_with(disk.mount_point())
+            }
+        })
+        .max_by_key(|disk| disk.mount_point().components().count())
+}
+
+/// Measure the current usage of `disk`.
+pub fn measure(disk: &Disk) -> UsageRecord {
+    let free = disk.available_space();
+    let used = disk.total_space().saturating_sub(free);
+    UsageRecord::new_with_current_time(used, free)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Local, TimeDelta};
+
+    use super::{USAGE_HISTORY_LEN, UsageHistory, UsageRecord};
+
+    #[test]
+    fn push_keeps_short_history() {
+        let mut history = UsageHistory::default();
+        let origin = Local::now();
+        for i in 0..(USAGE_HISTORY_LEN as i64 + 5) {
+            history.push(UsageRecord {
+                datetime: origin + TimeDelta::days(i),
+                used: i as u64,
+                free: 100,
+            });
+        }
+        assert_eq!(history.records.len(), USAGE_HISTORY_LEN);
+        assert_eq!(history.records.first().unwrap().used, 5);
+        assert_eq!(history.latest().unwrap().used, USAGE_HISTORY_LEN as u64 + 4);
+    }
+
+    #[test]
+    fn predict_full() {
+        let mut history = UsageHistory::default();
+        let origin = Local::now();
+        assert!(history.predict_full().is_none());
+        // 10 bytes per day, 1000 bytes in total
+        for i in 0..5 {
+            history.push(UsageRecord {
+                datetime: origin + TimeDelta::days(i),
+                used: 100 + 10 * i as u64,
+                free: 900 - 10 * i as u64,
+            });
+        }
+        let full = history.predict_full().unwrap();
+        assert_eq!((full - origin).num_days(), 90);
+
+        // decreasing usage never gets full
+        let mut history = UsageHistory::default();
+        for i in 0..5 {
+            history.push(UsageRecord {
+                datetime: origin + TimeDelta::days(i),
+                used: 100 - 10 * i as u64,
+                free: 900 + 10 * i as u64,
+            });
+        }
+        assert!(history.predict_full().is_none());
+
+        // 1 byte in a day on 1 TB drive
+        let mut history = UsageHistory::default();
+        for i in 0..2 {
+            history.push(UsageRecord {
+                datetime: origin + TimeDelta::days(i),
+                used: 100 + i as u64,
+                free: 1_000_000_000_000 - i as u64,
+            });
+        }
+        assert!(history.predict_full().is_none());
+    }
+}
@@ -63,13 +63,25 @@ pub fn format_summarized_duration(dt: chrono::Duration) -> String {
     }
 }
 
-pub fn duration_style(time: TimeDelta) -> Style {
+/// Level of staleness of `time` elapsed from the last backup, from 0 (fresh) to 5.
+pub fn staleness_level(time: TimeDelta) -> usize {
     match time {
-        x if x < TimeDelta::days(7) => Style::new().green(),
-        x if x < TimeDelta::days(14) => Style::new().yellow(),
-        x if x < TimeDelta::days(28) => Style::new().magenta(),
-        x if x < TimeDelta::days(28 * 3) => Style::new().red(),
-        x if x < TimeDelta::days(180) => Style::new().red().bold(),
+        x if x < TimeDelta::days(7) => 0,
+        x if x < TimeDelta::days(14) => 1,
+        x if x < TimeDelta::days(28) => 2,
+        x if x < TimeDelta::days(28 * 3) => 3,
+        x if x < TimeDelta::days(180) => 4,
+        _ => 5,
+    }
+}
+
+pub fn duration_style(time: TimeDelta) -> Style {
+    match staleness_level(time) {
+        0 => Style::new().green(),
+        1 => Style::new().yellow(),
+        2 => Style::new().magenta(),
+        3 => Style::new().red(),
+        4 => Style::new().red().bold(),
         _ => Style::new().on_red().black(),
     }
 }
@@ -139,6 +139,26 @@ mod integrated_test {
             .success()
             .stdout(predicate::str::contains("online_storage"));
 
+        // storage refresh
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("storage")
+            .arg("refresh")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("online_storage: used"));
+        assert!(std::fs::read_to_string(config_dir.path().join("storages.yml"))?.contains("free:"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("storage")
+            .arg("list")
+            .arg("-l")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("%").and(predicate::str::contains("used:")));
+
         // backup add
         let target_from = storage.join("foo/bar");
         let target_to = storage.join("aaa/bbb/ccc");
@@ -174,6 +194,19 @@ mod integrated_test {
             .success()
             .stdout(predicate::str::contains("sample_backup"));
 
+        // backup preflight
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("backup")
+            .arg("preflight")
+            .arg("sample_backup")
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("sample_backup").and(predicate::str::contains("free")),
+            );
+
         Command::cargo_bin("xdbm")?
             .arg("-c")
             .arg(config_dir.path())
@@ -183,88 +216,1592 @@ mod integrated_test {
             .arg("0")
             .assert()
             .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "sample_backup", "0"])
+            .assert()
+            .success();
+
+        // explicit status
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args([
+                "backup",
+                "done",
+                "sample_backup",
+                "0",
+                "--status",
+                "skipped",
+            ])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Skipped"));
+
+        // log retention, keeping the latest successful log besides the skipped one
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "prune-logs", "sample_backup", "--keep-last", "1"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("pruned 1 logs"));
+        assert!(
+            std::fs::read_to_string(config_dir.path().join("backups/testdev.yml"))?
+                .contains("summaries")
+        );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "retention", "sample_backup", "--keep-last", "1"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "sample_backup", "0"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("3/4 succeeded"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "prune-logs"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("sample_backup: pruned 0 logs"));
+
+        // exit code mapping
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("foo"))
+            .arg("--dest")
+            .arg(storage.join("aaa"))
+            .args(["--exit-code", "24=partial"])
+            .args(["rsync_backup", "external", "rsync"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "rsync_backup", "24"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("Partial").and(predicate::str::contains("exit code 24")),
+            );
+
+        // logs are stored separately from the backups file
+        let logs_file = config_dir.path().join("logs/testdev/rsync_backup.jsonl");
+        assert!(fs::read_to_string(&logs_file)?.contains("\"exit_code\":24"));
+        assert!(
+            !fs::read_to_string(config_dir.path().join("backups/testdev.yml"))?
+                .contains("datetime")
+        );
+
+        // logs in the old format are merged and moved by migrate
+        fs::remove_file(&logs_file)?;
+        let backups_file = config_dir.path().join("backups/testdev.yml");
+        let legacy = fs::read_to_string(&backups_file)?.replace(
+            "    name: rsync_backup\n",
+            "    name: rsync_backup\n    logs:\n    - datetime: 2024-01-01T00:00:00+09:00\n      status: Success\n      log: ''\n",
+        );
+        fs::write(&backups_file, legacy)?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("runs: 1/1 succeeded"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("migrate")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Migrated"));
+        // compare the instant since the offset follows the local timezone
+        let migrated: serde_json::Value = serde_json::from_str(
+            fs::read_to_string(&logs_file)?
+                .lines()
+                .next()
+                .context("No logs migrated")?,
+        )?;
+        assert_eq!(
+            chrono::DateTime::parse_from_rfc3339(
+                migrated["datetime"].as_str().context("No datetime")?
+            )?,
+            chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00+09:00")?
+        );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("migrate")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Already up to date."));
+
+        // html report
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "rsync_backup", "0"])
+            .args(["--duration", "42", "--bytes", "1024"])
+            .assert()
+            .success();
+        let report_dir = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("report")
+            .arg("--html")
+            .arg(report_dir.path())
+            .assert()
+            .success();
+        for page in [
+            "index.html",
+            "storages.html",
+            "matrix.html",
+            "violations.html",
+        ] {
+            assert!(report_dir.path().join(page).exists());
+        }
+        let backup_page =
+            fs::read_to_string(report_dir.path().join("backups/testdev/rsync_backup.html"))?;
+        assert!(backup_page.contains("<svg") && backup_page.contains("42s"));
+        assert!(
+            fs::read_to_string(report_dir.path().join("matrix.html"))?
+                .contains("backups/testdev/sample_backup.html")
+        );
+
+        // prometheus metrics
+        let metrics_file = report_dir.path().join("xdbm.prom");
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("metrics")
+            .arg("--prometheus")
+            .arg(&metrics_file)
+            .assert()
+            .success();
+        let metrics = fs::read_to_string(&metrics_file)?;
+        assert!(metrics.contains("xdbm_backup_last_success_timestamp{backup=\"rsync_backup\""));
+        assert!(metrics.contains("xdbm_backup_last_duration_seconds{"));
 
         Ok(())
     }
 
     #[test]
-    fn two_devices_with_same_name() -> Result<()> {
-        // 1st device
-        let config_dir_1 = assert_fs::TempDir::new()?;
+    #[cfg(unix)]
+    fn backup_run_with_hooks() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
         setup_gitconfig()?;
-        let mut cmd1 = Command::cargo_bin("xdbm")?;
-        cmd1.arg("-c")
-            .arg(config_dir_1.path())
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
             .arg("init")
-            .arg("first");
-        cmd1.assert().success().stdout(predicate::str::contains(""));
+            .arg("testdev")
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "me", "--capacity", "1000000000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        let target_from = storage.join("from");
+        let target_to = storage.join("to");
+        DirBuilder::new().recursive(true).create(&target_from)?;
+        DirBuilder::new().recursive(true).create(&target_to)?;
+        fs::write(target_from.join("file"), "content")?;
+        let hook_log = storage.join("hook.log");
 
-        // bare-repo
-        let bare_repo_dir = assert_fs::TempDir::new()?;
-        let _bare_repo = Repository::init_bare(&bare_repo_dir)?;
-        // push to bare repository
-        let repo_1 = Repository::open(&config_dir_1)?;
-        let upstream_name = "remote";
-        let mut repo_1_remote =
-            repo_1.remote(upstream_name, bare_repo_dir.path().to_str().unwrap())?;
-        repo_1_remote.push(&[repo_1.head().unwrap().name().unwrap()], None)?;
-        trace!("bare repo {:?}", bare_repo_dir.display());
-        println!("{:?}", bare_repo_dir.read_dir()?);
-        // set up upstream branch
-        let (mut repo_1_branch, _branch_type) = repo_1.branches(None)?.next().unwrap()?;
-        println!("head {}", repo_1.head().unwrap().name().unwrap());
-        repo_1_branch.set_upstream(Some(
+        // global hooks
+        fs::write(
+            config_dir.join("hooks.yml"),
             format!(
-                "{}/{}",
-                upstream_name,
-                repo_1_branch.name().unwrap().unwrap()
-            )
-            .as_str(),
-        ))?;
+                "pre:\n- echo global-pre >> {log}\npost:\n- echo global-post >> {log}\n",
+                log = hook_log.display()
+            ),
+        )?;
+
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(&target_from)
+            .arg("--dest")
+            .arg(&target_to)
+            .arg("--pre-hook")
+            .arg(format!(
+                "echo pre $XDBM_BACKUP_NAME >> {}",
+                hook_log.display()
+            ))
+            .arg("--post-hook")
+            .arg(format!(
+                "echo post $XDBM_BACKUP_STATUS >> {}",
+                hook_log.display()
+            ))
+            .args(["copy_backup", "shell", "cp"])
+            .arg("cp $XDBM_BACKUP_SRC/file $XDBM_BACKUP_DEST/")
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "run", "copy_backup"])
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(target_to.join("file"))?, "content");
+        assert_eq!(
+            fs::read_to_string(&hook_log)?,
+            "global-pre\npre copy_backup\npost Success\nglobal-post\n"
+        );
+
+        // the whole source is recorded even if the destination is up to date
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "run", "copy_backup"])
+            .assert()
+            .success();
+        let logs_file = fs::read_to_string(
+            config_dir
+                .join("logs")
+                .join("testdev")
+                .join("copy_backup.jsonl"),
+        )?;
+        for line in logs_file.lines() {
+            let log: serde_json::Value = serde_json::from_str(line)?;
+            assert_eq!(log["bytes"], 7);
+        }
+
+        // failing pre-hook aborts the backup
+        fs::remove_file(&hook_log)?;
+        fs::remove_file(target_to.join("file"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(&target_from)
+            .arg("--dest")
+            .arg(&target_to)
+            .args(["--pre-hook", "false"])
+            .args(["failing_backup", "shell", "cp"])
+            .arg("cp $XDBM_BACKUP_SRC/file $XDBM_BACKUP_DEST/")
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "run", "failing_backup"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Pre-hook failed"));
+        assert!(!target_to.join("file").exists());
+        assert_eq!(fs::read_to_string(&hook_log)?, "global-pre\nglobal-post\n");
+        let logs_file = fs::read_to_string(
+            config_dir
+                .join("logs")
+                .join("testdev")
+                .join("failing_backup.jsonl"),
+        )?;
+        assert!(logs_file.contains("Failure") && logs_file.contains("pre-hook failed"));
+
+        // externally invoked backup can't be run
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(&target_from)
+            .arg("--dest")
+            .arg(&target_to)
+            .args(["external_backup", "external", "rsync"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "run", "external_backup"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("backup done"));
 
-        // 2nd device
-        let config_dir_2 = assert_fs::TempDir::new()?;
-        setup_gitconfig()?;
-        let mut cmd2 = Command::cargo_bin("xdbm")?;
-        cmd2.arg("-c")
-            .arg(config_dir_2.path())
-            .arg("init")
-            .arg("first")
-            .arg("-r")
-            .arg(bare_repo_dir.path());
-        cmd2.assert().failure();
         Ok(())
     }
 
     #[test]
-    fn directory_without_parent() -> Result<()> {
-        // 1st device
-        let config_dir_1 = assert_fs::TempDir::new()?;
+    #[cfg(unix)]
+    fn scheduled_backups() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
         setup_gitconfig()?;
-        let mut cmd1 = Command::cargo_bin("xdbm")?;
-        cmd1.arg("-c")
-            .arg(config_dir_1.path())
-            .arg("init")
-            .arg("first");
-        cmd1.assert().success().stdout(predicate::str::contains(""));
-
-        // add storage
-        let sample_storage = assert_fs::TempDir::new()?;
-        let mut cmd_add_storage = Command::cargo_bin("xdbm")?;
-        cmd_add_storage
+        Command::cargo_bin("xdbm")?
             .arg("-c")
-            .arg(config_dir_1.path())
-            .arg("storage")
-            .arg("add")
-            .arg("directory")
-            .arg("--alias")
-            .arg("gdrive")
-            .arg("gdrive")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("src"))?;
+        DirBuilder::new().create(storage.join("dest"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .arg("--dest")
+            .arg(storage.join("dest"))
+            .args(["--schedule", "6h"])
+            .args(["shell_backup", "shell", "cp"])
+            .arg("touch $XDBM_BACKUP_DEST/done")
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .arg("--dest")
+            .arg(storage.join("dest"))
+            .args(["external_backup", "external", "rsync"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "schedule", "external_backup", "weekly"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "schedule", "shell_backup", "often"])
+            .assert()
+            .failure();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("when: 6h"));
+
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["schedule", "export"])
+            .assert()
+            .failure();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["schedule", "export", "--cron"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("0 */6 * * * ")
+                    .and(predicate::str::contains("backup run 'shell_backup'"))
+                    .and(predicate::str::contains("external_backup").not()),
+            )
+            .stderr(predicate::str::contains(
+                "external_backup is externally invoked",
+            ));
+        let units = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["schedule", "export", "--systemd", "--output"])
+            .arg(units.path())
+            .assert()
+            .success();
+        assert!(
+            fs::read_to_string(units.join("xdbm-backup-shell_backup.service"))?
+                .contains("backup run \"shell_backup\"")
+        );
+        assert!(
+            fs::read_to_string(units.join("xdbm-backup-shell_backup.timer"))?
+                .contains("OnCalendar=*-*-* 00/6:00:00")
+        );
+        assert!(!units.join("xdbm-backup-external_backup.timer").exists());
+
+        // daemon runs due backups once
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["daemon", "--once"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("shell_backup: running"));
+        assert!(storage.join("dest/done").exists());
+        fs::remove_file(storage.join("dest/done"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["daemon", "--once"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("running").not());
+        assert!(!storage.join("dest/done").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn notifications() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("src"))?;
+        DirBuilder::new().create(storage.join("dest"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .arg("--dest")
+            .arg(storage.join("dest"))
+            .args(["sample_backup", "external", "rsync"])
+            .assert()
+            .success();
+
+        // not configured
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("notify")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("notify.yml"));
+
+        // local http stub receiving one webhook request
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let server = std::thread::spawn(move || -> Result<String> {
+            use std::io::{BufRead, BufReader, Read};
+            let (stream, _) = listener.accept()?;
+            let mut reader = BufReader::new(stream.try_clone()?);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line)?;
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse()?;
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            (&stream).write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")?;
+            Ok(String::from_utf8(body)?)
+        });
+
+        let notified = config_dir.path().join("notified.txt");
+        fs::write(
+            config_dir.path().join("notify.yml"),
+            format!(
+                "subject: \"{{name}} {{status}}\"\nmessage: \"{{name}}: {{reason}} ({{log}})\"\nsinks:\n- type: command\n  command: cat >> {}\n- type: webhook\n  url: http://127.0.0.1:{}/hook\n",
+                notified.display(),
+                port
+            ),
+        )?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["notify", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("sample_backup -"));
+        assert!(!notified.exists());
+
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "sample_backup", "1", "--log", "disk full"])
+            .assert()
+            .success();
+        assert_eq!(
+            fs::read_to_string(&notified)?,
+            "sample_backup: finished with Failure (disk full)"
+        );
+        let body = server.join().unwrap()?;
+        assert!(body.contains("\"backup\":\"sample_backup\""));
+        assert!(body.contains("\"status\":\"Failure\""));
+
+        // success is not notified by default
+        fs::remove_file(&notified)?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "sample_backup", "0"])
+            .assert()
+            .success();
+        assert!(!notified.exists());
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("notify")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No failed or overdue backups."));
+
+        Ok(())
+    }
+
+    #[test]
+    fn device_management() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("src"))?;
+        DirBuilder::new().create(storage.join("dest"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .arg("--dest")
+            .arg(storage.join("dest"))
+            .args(["sample_backup", "external", "rsync"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "sample_backup", "0"])
+            .assert()
+            .success();
+
+        // rename
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["device", "rename", "testdev", "laptop"])
+            .assert()
+            .success();
+        assert_eq!(fs::read_to_string(config_dir.join("devname"))?, "laptop\n");
+        assert!(!config_dir.join("backups/testdev.yml").exists());
+        assert!(
+            fs::read_to_string(config_dir.join("backups/laptop.yml"))?.contains("device: laptop")
+        );
+        assert!(config_dir.join("logs/laptop/sample_backup.jsonl").exists());
+        assert!(!config_dir.join("logs/testdev").exists());
+        assert!(fs::read_to_string(config_dir.join("storages.yml"))?.contains("laptop:"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["device", "list"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("* laptop")
+                    .and(predicate::str::contains("1 backups"))
+                    .and(predicate::str::contains("storages: online_storage"))
+                    .and(predicate::str::contains("ago")),
+            );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[laptop]"));
+        assert!(
+            Repository::open(config_dir.path())?
+                .statuses(None)?
+                .iter()
+                .all(|status| status.status().is_ignored())
+        );
+
+        // refresh
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["device", "refresh"])
+            .assert()
+            .success();
+
+        // remove
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["device", "remove", "laptop"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("current device"));
+        let mut devices_file = fs::OpenOptions::new()
+            .append(true)
+            .open(config_dir.join("devices.yml"))?;
+        devices_file.write_all(
+            b"- name: old\n  os_name: unknown\n  os_version: unknown\n  hostname: unknown\n",
+        )?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["device", "remove", "old"])
+            .assert()
+            .success();
+        assert!(!fs::read_to_string(config_dir.join("devices.yml"))?.contains("old"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn local_settings() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+
+        // long format by default
+        fs::write(
+            config_dir.join("settings.local.yml"),
+            "color: never\nlong: true\n",
+        )?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("sample_provider"));
+        assert!(
+            Repository::open(config_dir.path())?
+                .statuses(None)?
+                .iter()
+                .all(|status| status.status().is_ignored())
+        );
+
+        // auto sync runs after commit
+        fs::write(
+            config_dir.join("settings.local.yml"),
+            "remote: nonexistent\nauto_sync: true\n",
+        )?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "refresh"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Auto sync failed"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn config_dir_env() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .env("XDBM_CONFIG_DIR", config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .env("XDBM_CONFIG_DIR", config_dir.path())
+            .arg("path")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                config_dir.path().to_string_lossy(),
+            ));
+
+        // completers resolve the same config dir
+        Command::cargo_bin("xdbm")?
+            .env("COMPLETE", "fish")
+            .env("XDBM_CONFIG_DIR", config_dir.path())
+            .args(["--", "xdbm", "device", "rename", ""])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("testdev"));
+        Command::cargo_bin("xdbm")?
+            .env("COMPLETE", "fish")
+            .env_remove("XDBM_CONFIG_DIR")
+            .args(["--", "xdbm", "-c"])
+            .arg(config_dir.path())
+            .args(["device", "rename", ""])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("testdev"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn two_devices_with_same_name() -> Result<()> {
+        // 1st device
+        let config_dir_1 = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        let mut cmd1 = Command::cargo_bin("xdbm")?;
+        cmd1.arg("-c")
+            .arg(config_dir_1.path())
+            .arg("init")
+            .arg("first");
+        cmd1.assert().success().stdout(predicate::str::contains(""));
+
+        // bare-repo
+        let bare_repo_dir = assert_fs::TempDir::new()?;
+        let _bare_repo = Repository::init_bare(&bare_repo_dir)?;
+        // push to bare repository
+        let repo_1 = Repository::open(&config_dir_1)?;
+        let upstream_name = "remote";
+        let mut repo_1_remote =
+            repo_1.remote(upstream_name, bare_repo_dir.path().to_str().unwrap())?;
+        repo_1_remote.push(&[repo_1.head().unwrap().name().unwrap()], None)?;
+        trace!("bare repo {:?}", bare_repo_dir.display());
+        println!("{:?}", bare_repo_dir.read_dir()?);
+        // set up upstream branch
+        let (mut repo_1_branch, _branch_type) = repo_1.branches(None)?.next().unwrap()?;
+        println!("head {}", repo_1.head().unwrap().name().unwrap());
+        repo_1_branch.set_upstream(Some(
+            format!(
+                "{}/{}",
+                upstream_name,
+                repo_1_branch.name().unwrap().unwrap()
+            )
+            .as_str(),
+        ))?;
+
+        // 2nd device
+        let config_dir_2 = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        let mut cmd2 = Command::cargo_bin("xdbm")?;
+        cmd2.arg("-c")
+            .arg(config_dir_2.path())
+            .arg("init")
+            .arg("first")
+            .arg("-r")
+            .arg(bare_repo_dir.path());
+        cmd2.assert().failure();
+        Ok(())
+    }
+
+    #[test]
+    fn directory_without_parent() -> Result<()> {
+        // 1st device
+        let config_dir_1 = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        let mut cmd1 = Command::cargo_bin("xdbm")?;
+        cmd1.arg("-c")
+            .arg(config_dir_1.path())
+            .arg("init")
+            .arg("first");
+        cmd1.assert().success().stdout(predicate::str::contains(""));
+
+        // add storage
+        let sample_storage = assert_fs::TempDir::new()?;
+        let mut cmd_add_storage = Command::cargo_bin("xdbm")?;
+        cmd_add_storage
+            .arg("-c")
+            .arg(config_dir_1.path())
+            .arg("storage")
+            .arg("add")
+            .arg("directory")
+            .arg("--alias")
+            .arg("gdrive")
+            .arg("gdrive")
             .arg(sample_storage.path());
         cmd_add_storage
             .assert()
             .failure()
-            .stderr(predicate::str::contains("No storages found"));
+            .stderr(predicate::str::contains("No storages found"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn network_share() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "dev"])
+            .assert()
+            .success();
+
+        // share which is not mounted
+        let mount_point = config_dir.path().join("nas_mount");
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "network", "nas"])
+            .arg(&mount_point)
+            .args(["--server", "nas.local", "--share", "backup"])
+            .args(["--protocol", "smb", "--alias", "nas"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("not mounted"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("N : nas")
+                    .and(predicate::str::contains("(not mounted)"))
+                    .and(predicate::str::contains("smb://nas.local/backup")),
+            );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rclone_remote() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("src"))?;
+        fs::write(storage.join("src").join("file.txt"), "data")?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+
+        // use the real rclone with a local filesystem remote if available,
+        // otherwise a stub which handles the local remote
+        let rclone = if std::process::Command::new("rclone")
+            .arg("version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+        {
+            path::PathBuf::from("rclone")
+        } else {
+            let stub = config_dir.path().join("rclone_stub.sh");
+            fs::write(
+                &stub,
+                "#!/bin/sh\ncase \"$1\" in\nabout) echo '{\"total\": 1000000000, \"used\": 1000, \"free\": 999999000}' ;;\ncopy|sync) mkdir -p \"${3#:local:}\" && cp -R \"$2/.\" \"${3#:local:}\" ;;\n*) exit 1 ;;\nesac\n",
+            )?;
+            fs::set_permissions(&stub, fs::Permissions::from_mode(0o755))?;
+            stub
+        };
+        let remote_dir = assert_fs::TempDir::new()?;
+        let remote = format!(":local:{}", remote_dir.path().display());
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .env("XDBM_RCLONE", &rclone)
+            .args(["storage", "add", "rclone", "cloud", &remote])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("R : cloud")
+                    .and(predicate::str::contains(remote.as_str()))
+                    .and(predicate::str::contains("used: ")),
+            );
+
+        // absolute path is not allowed with --dest-storage
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .args(["--dest", "/docs", "--dest-storage", "cloud"])
+            .args(["to_cloud", "rclone"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("must be relative"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .env("XDBM_RCLONE", &rclone)
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .args(["--dest", "docs", "--dest-storage", "cloud"])
+            .args(["to_cloud", "rclone"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!("{}/docs", remote)));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .env("XDBM_RCLONE", &rclone)
+            .args(["backup", "run", "to_cloud"])
+            .assert()
+            .success();
+        assert_eq!(
+            fs::read_to_string(remote_dir.path().join("docs").join("file.txt"))?,
+            "data"
+        );
+        // missing rclone is recorded as a failure
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .env("XDBM_RCLONE", config_dir.path().join("missing_rclone"))
+            .args(["backup", "run", "to_cloud"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Failed to execute"));
+        let logs_file = fs::read_to_string(
+            config_dir
+                .join("logs")
+                .join("testdev")
+                .join("to_cloud.jsonl"),
+        )?;
+        let last: serde_json::Value = serde_json::from_str(logs_file.lines().last().unwrap())?;
+        assert_eq!(last["status"], "Failure");
+
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .env("XDBM_RCLONE", &rclone)
+            .args(["storage", "refresh"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("cloud: used"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn archive_storage() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("src"))?;
+        DirBuilder::new().create(storage.join("restic"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "archive", "repo"])
+            .arg(storage.join("restic"))
+            .args(["--format", "restic", "--encrypted"])
+            .args(["--repository-id", "abc123"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("A : repo")
+                    .and(predicate::str::contains("online_storage"))
+                    .and(predicate::str::contains("restic, encrypted, id abc123")),
+            );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "bind", "--alias", "repo", "--path"])
+            .arg(storage.path())
+            .arg("repo")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Bind the parent instead"));
+
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("src"))
+            .args(["--dest", "docs", "--dest-storage", "repo"])
+            .args(["to_repo", "external", "restic"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["status", "--copies"])
+            .arg(storage.join("src"))
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("to_repo")
+                    .and(predicate::str::contains("restic::docs"))
+                    .and(predicate::str::contains("not browsable")),
+            );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("check")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Warning").not());
+
+        fs::remove_dir(storage.join("restic"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("check")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archive repo is not found"))
+            .stdout(predicate::str::contains("Check passed with 1 warning"))
+            .stdout(predicate::str::contains("All check passed").not());
+
+        Ok(())
+    }
+
+    #[test]
+    fn offline_media() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("photos"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "offline", "disc"])
+            .args(["--kind", "bluray", "--label", "BD-001"])
+            .args(["--location", "home safe", "--written", "2026-01-03"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("M : disc").and(predicate::str::contains(
+                    "Blu-ray at home safe (on-site), written 2026-01-03",
+                )),
+            );
+
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("photos"))
+            .args(["--dest", "photos", "--dest-storage", "disc"])
+            .args(["to_disc", "external", "burn"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "done", "to_disc", "0"])
+            .assert()
+            .success();
+        // media are not paths which commands can write into
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("photos"))
+            .args(["--dest", "photos", "--dest-storage", "disc"])
+            .args(["burn_disc", "shell", "cp"])
+            .arg("cp -r $XDBM_BACKUP_SRC $XDBM_BACKUP_DEST")
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "run", "burn_disc"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("can't write into"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("to_disc"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "list", "-l"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("BD-001:/photos"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["status", "--copies"])
+            .arg(storage.join("photos"))
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("disc: BD-001:/photos")
+                    .and(predicate::str::contains("Blu-ray at home safe")),
+            );
+
+        // media are moved like physical drives
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "move", "disc", "bank", "--offsite"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("disc was at home safe (on-site)")
+                    .and(predicate::str::contains("Moved disc to bank (off-site)")),
+            );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["status", "--copies"])
+            .arg(storage.join("photos"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Blu-ray at bank (off-site)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn storage_move() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        // only physical drives and offline media have locations
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "move", "online_storage", "home safe"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "neither a physical drive nor offline media",
+            ));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "move", "missing", "home safe", "--offsite"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No storage has name missing"));
+
+        DirBuilder::new().create(storage.join("photos"))?;
+        let backup_drive = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "physical", "hdd"])
+            .arg(backup_drive.path())
+            .args(["-k", "HDD", "-c", "2000000", "--fs", "exfat"])
+            .args(["--removable", "true", "-a", "sdc1"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("photos"))
+            .arg("--dest")
+            .arg(backup_drive.path())
+            .args(["photos", "external", "rsync"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "move", "hdd", "home safe"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Moved hdd to home safe (on-site)"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "move", "hdd", "parents' house", "--offsite"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("hdd was at home safe (on-site)"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["status", "--copies"])
+            .arg(storage.join("photos"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[off-site: parents' house]"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list", "--long"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "location: parents' house (off-site",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn drive_lifecycle() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        DirBuilder::new().create(storage.join("photos"))?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "online"])
+            .args(["--provider", "sample_provider", "--capacity", "1000000"])
+            .args(["--alias", "alias", "online_storage"])
+            .arg(storage.path())
+            .assert()
+            .success();
+        // only physical drives have lifecycle info
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "retire", "online_storage"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not a physical drive"));
+        // nothing to edit
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "edit", "online_storage"])
+            .assert()
+            .failure();
+
+        let backup_drive = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "physical", "hdd"])
+            .arg(backup_drive.path())
+            .args(["-k", "HDD", "-c", "2000000", "--fs", "exfat"])
+            .args(["--removable", "true", "-a", "sdc1"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["backup", "add", "--src"])
+            .arg(storage.join("photos"))
+            .arg("--dest")
+            .arg(backup_drive.path())
+            .args(["photos", "external", "rsync"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("check")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("All check passed"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "edit", "hdd", "--model", "WD Red"])
+            .args(["--serial", "X1", "--purchased", "2024-04-01"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "retire", "hdd"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list", "--long"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("(retired)").and(predicate::str::contains(
+                    "model: WD Red, serial: X1, purchased: 2024-04-01, status: retired",
+                )),
+            );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("check")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "The destination of backup photos is on the retired drive hdd",
+            ))
+            .stdout(predicate::str::contains("Check passed with 1 warning"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn physical_drive() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        let storage = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "physical", "ssd"])
+            .arg(storage.path())
+            .args(["--kind", "SSD", "--capacity", "1000000", "--fs", "ext4"])
+            .args(["--removable", "true", "--alias", "sdb1"])
+            .assert()
+            .success();
+        let backup_drive = assert_fs::TempDir::new()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "physical", "hdd"])
+            .arg(backup_drive.path())
+            .args(["-k", "HDD", "-c", "2000000", "--fs", "exfat"])
+            .args(["--removable", "true", "-a", "sdc1"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("P+: ssd").and(predicate::str::contains("P+: hdd")));
+        // --from-mount takes the drive from system info
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "add", "physical", "missing", "--from-mount"])
+            .arg(storage.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No drive is mounted at"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn drives() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        setup_gitconfig()?;
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["init", "testdev"])
+            .assert()
+            .success();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["drive", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No drives found"));
+        for disk in ["disk1", "disk2"] {
+            Command::cargo_bin("xdbm")?
+                .arg("-c")
+                .arg(config_dir.path())
+                .args(["drive", "add", disk])
+                .assert()
+                .success();
+        }
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["drive", "add", "tank", "--kind", "pool"])
+            .args(["--member", "disk1", "--member", "disk2"])
+            .args(["--notes", "NAS"])
+            .assert()
+            .success();
+        // members should be added first
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["drive", "add", "mirror", "--kind", "raid"])
+            .args(["--member", "disk3"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No drive has name disk3"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["drive", "add", "disk1"])
+            .assert()
+            .failure();
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["drive", "list"])
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("pool [disk1, disk2]")
+                    .and(predicate::str::contains("NAS")),
+            );
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .args(["storage", "edit", "missing", "--drive", "disk3"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No drive has name disk3"));
+        Command::cargo_bin("xdbm")?
+            .arg("-c")
+            .arg(config_dir.path())
+            .arg("check")
+            .assert()
+            .success();
 
         Ok(())
     }